@@ -1,3 +1,10 @@
+use std::{io::ErrorKind, time::SystemTime};
+
+use crabmat::{
+    lock::{self, Holder, Lock},
+    Card, Check, Column, History, Kanban, KanbanError, LoadError,
+};
+use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
     style::{Modifier, Style},
     widgets::Block,
@@ -5,7 +12,9 @@ use ratatui::{
 use tui_textarea::{Input, TextArea};
 
 use crate::{
-    kanban::{Card, Column, Kanban},
+    command::Completion,
+    config::Config,
+    search::Search,
     vim::{Mode, Transition, Vim},
 };
 
@@ -13,6 +22,12 @@ pub enum CurrentScreen {
     Main,
     Card(bool),
     Col(bool),
+    Search,
+    Command,
+    LabelFilter,
+    Details,
+    Archive,
+    Conflict,
     DeleteCard(Card),
     DeleteCol(Column),
 }
@@ -20,14 +35,52 @@ pub enum CurrentScreen {
 pub enum CurrentlyEditing {
     Title,
     Description,
+    Due,
+    Limit,
+    Labels,
+    NewLabel,
+    Checklist,
+    Check(bool),
 }
 
 pub struct App {
     pub path: String,
+    pub config: Config,
     pub vim: Vim,
     pub title_input: TextArea<'static>,
     pub description_input: TextArea<'static>,
+    pub due_input: TextArea<'static>,
+    pub limit_input: TextArea<'static>,
+    pub check_input: TextArea<'static>,
+    pub checklist: Vec<Check>,
+    pub labels: Vec<String>,
+    pub card_labels: Vec<String>,
+    pub label_input: TextArea<'static>,
+    pub label_filter: Vec<String>,
+    pub selected_label: usize,
+    pub search_input: TextArea<'static>,
+    pub archive_input: TextArea<'static>,
+    pub archive_search: Option<Search>,
+    pub searching_archive: bool,
+    pub selected_archived: usize,
+    pub search: Option<Search>,
+    pub command_input: TextArea<'static>,
+    pub command_history: Vec<String>,
+    pub command_history_idx: Option<usize>,
+    pub completion: Option<Completion>,
+    pub message: Option<String>,
+    pub quit: bool,
     pub kanban: Kanban,
+    /// The modification time and size of the board file when it was last loaded or saved here,
+    /// to tell when something else changed it.
+    pub on_disk: Option<(SystemTime, u64)>,
+    /// Whether a save was held back because the file had changed on disk in the meantime.
+    pub conflict: bool,
+    /// This session's lock on the board file, see [`crabmat::lock`].
+    pub lock: Option<Lock>,
+    /// Who else has the board open, when it could not be locked. The board is read-only then.
+    pub locked_by: Option<Holder>,
+    pub history: History,
     pub current_screen: CurrentScreen,
     pub currently_editing: Option<CurrentlyEditing>,
     pub columns_start: usize,
@@ -38,7 +91,7 @@ pub struct App {
 }
 
 impl App {
-    pub fn new(path: String, kanban: Kanban) -> App {
+    pub fn new(path: String, kanban: Kanban, config: Config) -> App {
         let mut title_input = TextArea::default();
         title_input.set_style(Style::default());
         title_input.set_cursor_style(Style::default());
@@ -47,13 +100,42 @@ impl App {
         description_input.set_style(Style::default());
         description_input.set_cursor_style(Style::default());
         description_input.set_cursor_line_style(Style::default());
+        let mut check_input = TextArea::default();
+        check_input.set_cursor_line_style(Style::default());
 
         App {
+            on_disk: disk_stamp(&path),
+            conflict: false,
+            lock: None,
+            locked_by: None,
             kanban,
+            history: History::default(),
             path,
+            config,
             vim: Vim::new(Mode::Normal),
             title_input,
             description_input,
+            due_input: TextArea::default(),
+            limit_input: TextArea::default(),
+            check_input,
+            checklist: Vec::new(),
+            labels: Vec::new(),
+            card_labels: Vec::new(),
+            label_input: TextArea::default(),
+            label_filter: Vec::new(),
+            selected_label: 0,
+            search_input: TextArea::default(),
+            archive_input: TextArea::default(),
+            archive_search: None,
+            searching_archive: false,
+            selected_archived: 0,
+            search: None,
+            command_input: TextArea::default(),
+            command_history: Vec::new(),
+            command_history_idx: None,
+            completion: None,
+            message: None,
+            quit: false,
             current_screen: CurrentScreen::Main,
             currently_editing: None,
             columns_start: 0,
@@ -64,19 +146,334 @@ impl App {
         }
     }
 
+    /// Saves the board, unless the file changed on disk since it was last loaded or saved here,
+    /// in which case the save is held back until the conflict prompt is answered.
     pub fn save_kanban(&mut self) {
-        self.kanban.save(&self.path);
+        if self.locked_by.is_some() {
+            // Never written while read-only, edits are refused before they get here.
+        } else if self.conflict || disk_stamp(&self.path) != self.on_disk {
+            self.conflict = true;
+        } else {
+            self.write_kanban();
+        }
 
         self.title_input = TextArea::default();
         self.description_input = TextArea::default();
+        self.check_input = TextArea::default();
+        self.checklist = Vec::new();
+        self.selected_check = 0;
         self.currently_editing = None;
     }
 
+    fn write_kanban(&mut self) {
+        match self.kanban.save(&self.path) {
+            Ok(()) => {
+                self.on_disk = disk_stamp(&self.path);
+                self.conflict = false;
+            }
+            Err(e) => self.message = Some(format!("could not save {}: {}", self.path, e)),
+        }
+    }
+
+    /// Picks up changes made to the board file by something else: reloads it with a fresh undo
+    /// history when nothing here is waiting to be saved, and asks what to do when both sides
+    /// changed. Only acts on the
+    /// board itself, so an open editor or dialog is never pulled out from under the user.
+    pub fn watch_file(&mut self) {
+        if self.locked_by.is_some() {
+            match lock::holder(&self.path) {
+                None => {
+                    if self.take_lock(false).is_ok() {
+                        self.message = Some(format!("{} was closed elsewhere", self.path));
+                    }
+                }
+                Some(holder) => {
+                    if holder.is_stale() && self.message.is_none() {
+                        self.message = Some(format!(
+                            "{} is no longer running, :lock to edit {}",
+                            holder, self.path
+                        ));
+                    }
+                    self.locked_by = Some(holder);
+                }
+            }
+        }
+
+        if !matches!(self.current_screen, CurrentScreen::Main) {
+            return;
+        }
+        if self.conflict {
+            self.current_screen = CurrentScreen::Conflict;
+            return;
+        }
+
+        let stamp = disk_stamp(&self.path);
+        if stamp == self.on_disk {
+            return;
+        }
+        match Kanban::from_file(&self.path) {
+            Ok(kanban) => {
+                self.on_disk = stamp;
+                if kanban != self.kanban {
+                    // Undoing past the reload would quietly revert the other program's change
+                    // and save that over it.
+                    self.kanban = kanban;
+                    self.history.clear();
+                    self.clear_search();
+                    self.clamp_selection();
+                    self.message = Some(format!("reloaded {}, it changed on disk", self.path));
+                }
+            }
+            // Deleted. The next save here puts the board back.
+            Err(LoadError::Io(e)) if e.kind() == ErrorKind::NotFound => {
+                self.on_disk = stamp;
+                self.message = Some(format!("{} was deleted, saving recreates it", self.path));
+            }
+            // Unparsable, or caught halfway through a write by a less careful program. The file
+            // still counts as changed, so it is read again on the next check and saving here asks
+            // before overwriting it.
+            Err(e) => self.message = Some(format!("could not reload {}: {}", self.path, e)),
+        }
+    }
+
+    /// Says in the footer that the board is read-only, returning whether it is.
+    pub fn refuse_read_only(&mut self) -> bool {
+        let Some(holder) = &self.locked_by else {
+            return false;
+        };
+
+        self.message = Some(format!(
+            "read-only, {} has {} open (:lock to take over)",
+            holder, self.path
+        ));
+        true
+    }
+
+    /// Locks the board file for this session, taking the lock from its holder when `steal` is
+    /// set. Without the lock the board is read-only.
+    pub fn take_lock(&mut self, steal: bool) -> Result<(), String> {
+        self.lock = None;
+        let result = if steal {
+            lock::steal(&self.path).map(Ok)
+        } else {
+            lock::acquire(&self.path)
+        };
+
+        match result {
+            Ok(Ok(lock)) => {
+                self.lock = Some(lock);
+                self.locked_by = None;
+                Ok(())
+            }
+            Ok(Err(holder)) => {
+                let e = format!("{} is locked by {}", self.path, holder);
+                self.locked_by = Some(holder);
+                Err(e)
+            }
+            // Boards in places that cannot hold a lock file are edited without one.
+            Err(e) => {
+                self.locked_by = None;
+                Err(format!("could not lock {}: {}", self.path, e))
+            }
+        }
+    }
+
+    /// Answers the conflict prompt by writing the board as it is here over the file.
+    pub fn keep_mine(&mut self) {
+        self.conflict = false;
+        self.current_screen = CurrentScreen::Main;
+        self.write_kanban();
+    }
+
+    /// Answers the conflict prompt by loading the file, keeping the board as it was here in the
+    /// undo history.
+    pub fn take_theirs(&mut self) {
+        match Kanban::from_file(&self.path) {
+            Ok(kanban) => {
+                self.history
+                    .record(std::mem::replace(&mut self.kanban, kanban));
+                self.on_disk = disk_stamp(&self.path);
+                self.conflict = false;
+                self.current_screen = CurrentScreen::Main;
+                self.clear_search();
+                self.clamp_selection();
+                self.message = Some(format!(
+                    "reloaded {}, u brings your version back",
+                    self.path
+                ));
+            }
+            Err(e) => self.message = Some(format!("could not reload {}: {}", self.path, e)),
+        }
+    }
+
+    /// Applies a mutation to the board, recording the previous state for undo and saving the
+    /// result. The board is left untouched when the mutation fails.
+    pub fn update_kanban<T>(
+        &mut self,
+        update: impl FnOnce(&mut Kanban) -> Result<T, KanbanError>,
+    ) -> Result<T, KanbanError> {
+        let before = self.kanban.clone();
+        let result = match update(&mut self.kanban) {
+            Ok(result) => result,
+            Err(e) => {
+                self.kanban = before;
+                return Err(e);
+            }
+        };
+
+        if self.kanban != before {
+            self.history.record(before);
+        }
+        self.save_kanban();
+        Ok(result)
+    }
+
+    /// Explains a failed mutation for the footer, naming a full column by its title.
+    pub fn describe_error(&self, e: KanbanError) -> String {
+        match e {
+            KanbanError::ColumnFull(idx) => match self.kanban.get_column(idx) {
+                Some(column) => format!(
+                    "{} is at its WIP limit of {}",
+                    column.title,
+                    column.limit.unwrap_or_default()
+                ),
+                None => e.to_string(),
+            },
+            e => e.to_string(),
+        }
+    }
+
+    /// Points out in the footer when a column holds more cards than its WIP limit.
+    pub fn warn_over_limit(&mut self, column_idx: usize) {
+        let Some(column) = self.kanban.get_column(column_idx) else {
+            return;
+        };
+        if let (true, Some(limit)) = (column.is_over_limit(), column.limit) {
+            self.message = Some(format!(
+                "{} is over its WIP limit ({}/{})",
+                column.title,
+                column.cards.len(),
+                limit
+            ));
+        }
+    }
+
+    /// Moves a card to the end of another column, saying so in the footer when a WIP limit
+    /// refused the move or is now exceeded. Returns whether the card moved.
+    pub fn move_card(&mut self, column_idx: usize, new_column_idx: usize, card_idx: usize) -> bool {
+        match self.update_kanban(|kanban| kanban.move_card(column_idx, new_column_idx, card_idx)) {
+            Ok(()) => {
+                self.warn_over_limit(new_column_idx);
+                true
+            }
+            Err(e @ KanbanError::ColumnFull(_)) => {
+                self.message = Some(self.describe_error(e));
+                false
+            }
+            Err(_) => false,
+        }
+    }
+
+    pub fn undo(&mut self) {
+        if self.history.undo(&mut self.kanban) {
+            self.save_kanban();
+            self.clamp_selection();
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if self.history.redo(&mut self.kanban) {
+            self.save_kanban();
+            self.clamp_selection();
+        }
+    }
+
+    /// Replaces the board with another file's, starting with a fresh history and selection.
+    pub fn open(&mut self, path: &str, kanban: Kanban) {
+        self.path = path.to_string();
+        self.kanban = kanban;
+        self.on_disk = disk_stamp(path);
+        self.conflict = false;
+        self.history.clear();
+        self.clear_search();
+        self.selected_column = 0;
+        self.selected_card = 0;
+        self.columns_start = 0;
+        if let Err(e) = self.take_lock(false) {
+            self.message = Some(e);
+        }
+    }
+
+    /// Shows as many columns as fit in `width` cells, scrolling so the selected column stays in
+    /// view and no space is left empty while there are columns to the left to fill it.
+    pub fn fit_columns(&mut self, width: u16) {
+        let layout = &self.config.columns;
+        let weights = self
+            .kanban
+            .columns()
+            .iter()
+            .map(|column| layout.weight(&column.title))
+            .collect::<Vec<_>>();
+
+        // How many fit depends on which columns are shown, since they are not all as wide.
+        let mut start = self.columns_start.min(self.selected_column);
+        while start + 1 < weights.len()
+            && self.selected_column >= start + layout.visible(width, &weights[start..])
+        {
+            start += 1;
+        }
+        // Then back to the left while every column from there to the last still fits.
+        while start > 0 && layout.visible(width, &weights[start - 1..]) == weights.len() - start + 1
+        {
+            start -= 1;
+        }
+
+        self.columns_start = start;
+        self.columns_offset = layout.visible(width, &weights[start..]);
+    }
+
+    /// Scrolls the board as little as possible to bring the selected column into view.
+    fn scroll_to_selected_column(&mut self) {
+        if self.selected_column < self.columns_start {
+            self.columns_start = self.selected_column;
+        } else if self.selected_column >= self.columns_start + self.columns_offset {
+            self.columns_start = self.selected_column + 1 - self.columns_offset;
+        }
+    }
+
+    /// Keeps the selected column and card on the board after it changed underneath them.
+    pub fn clamp_selection(&mut self) {
+        let columns = self.kanban.columns().len();
+        if self.selected_column >= columns {
+            self.selected_column = columns.saturating_sub(1);
+        }
+        if self.columns_start > self.selected_column {
+            self.columns_start = self.selected_column;
+        }
+
+        let cards = self
+            .kanban
+            .get_column(self.selected_column)
+            .map_or(0, |column| column.cards.len());
+        if self.selected_card >= cards {
+            self.selected_card = cards.saturating_sub(1);
+        }
+    }
+
     pub fn stop_edit(&mut self) {
         self.title_input.select_all();
         self.title_input.cut();
         self.description_input.select_all();
         self.description_input.cut();
+        self.due_input = TextArea::default();
+        self.limit_input = TextArea::default();
+        self.check_input = TextArea::default();
+        self.checklist = Vec::new();
+        self.selected_check = 0;
+        self.labels = Vec::new();
+        self.card_labels = Vec::new();
+        self.label_input = TextArea::default();
+        self.selected_label = 0;
         self.current_screen = CurrentScreen::Main;
         self.currently_editing = None;
         self.title_input.set_cursor_line_style(Style::default());
@@ -97,6 +494,10 @@ impl App {
         self.description_input
             .set_cursor_line_style(Style::default());
         self.description_input.set_cursor_style(Style::default());
+        self.due_input.set_style(Style::default());
+        self.due_input.set_cursor_style(Style::default());
+        self.limit_input.set_style(Style::default());
+        self.limit_input.set_cursor_style(Style::default());
     }
 
     pub fn edit_description(&mut self) {
@@ -109,6 +510,229 @@ impl App {
             .set_cursor_style(Style::default().add_modifier(Modifier::REVERSED));
         self.title_input.set_style(Style::default());
         self.title_input.set_cursor_style(Style::default());
+        self.due_input.set_style(Style::default());
+        self.due_input.set_cursor_style(Style::default());
+    }
+
+    pub fn edit_due(&mut self) {
+        self.currently_editing = Some(CurrentlyEditing::Due);
+        self.due_input
+            .set_style(Style::default().add_modifier(Modifier::BOLD));
+        self.due_input.set_cursor_line_style(Style::default());
+        self.due_input
+            .set_cursor_style(Style::default().add_modifier(Modifier::REVERSED));
+        self.title_input.set_style(Style::default());
+        self.title_input.set_cursor_style(Style::default());
+        self.description_input.set_style(Style::default());
+        self.description_input.set_cursor_style(Style::default());
+    }
+
+    /// Focuses the WIP limit field of the column editor.
+    pub fn edit_limit(&mut self) {
+        self.currently_editing = Some(CurrentlyEditing::Limit);
+        self.limit_input
+            .set_style(Style::default().add_modifier(Modifier::BOLD));
+        self.limit_input.set_cursor_line_style(Style::default());
+        self.limit_input
+            .set_cursor_style(Style::default().add_modifier(Modifier::REVERSED));
+        self.title_input.set_style(Style::default());
+        self.title_input.set_cursor_style(Style::default());
+    }
+
+    /// Focuses the label picker of the card editor.
+    pub fn edit_labels(&mut self) {
+        self.currently_editing = Some(CurrentlyEditing::Labels);
+        self.vim = Vim::new(Mode::Normal);
+        self.title_input.set_style(Style::default());
+        self.title_input.set_cursor_style(Style::default());
+        self.description_input.set_style(Style::default());
+        self.description_input.set_cursor_style(Style::default());
+        self.due_input.set_style(Style::default());
+        self.due_input.set_cursor_style(Style::default());
+        self.label_input = TextArea::default();
+        self.refresh_labels();
+        if self.selected_label >= self.labels.len() {
+            self.selected_label = self.labels.len().saturating_sub(1);
+        }
+    }
+
+    /// Adds the palette and the card's own labels to those the picker lists.
+    pub fn refresh_labels(&mut self) {
+        let palette = self.kanban.labels().iter().map(|label| &label.name);
+        for label in palette.chain(&self.card_labels) {
+            if !self.labels.contains(label) {
+                self.labels.push(label.clone());
+            }
+        }
+    }
+
+    pub fn new_label(&mut self) {
+        self.label_input = TextArea::default();
+        self.label_input.set_cursor_line_style(Style::default());
+        self.label_input
+            .set_cursor_style(Style::default().add_modifier(Modifier::REVERSED));
+        self.currently_editing = Some(CurrentlyEditing::NewLabel);
+        self.vim = Vim::new(Mode::Normal);
+        self.update_vim(Input::from(KeyEvent::from(KeyCode::Char('A'))));
+    }
+
+    /// Adds the typed label to the picker and to the card, then returns to the picker.
+    pub fn save_label(&mut self) {
+        let name = self.label_input.lines().concat().trim().to_string();
+        if !name.is_empty() {
+            if !self.labels.contains(&name) {
+                self.labels.push(name.clone());
+            }
+            if !self.card_labels.contains(&name) {
+                self.card_labels.push(name.clone());
+            }
+            self.selected_label = self
+                .labels
+                .iter()
+                .position(|label| label == &name)
+                .unwrap_or(0);
+        }
+        self.edit_labels();
+    }
+
+    /// Toggles the selected label of the picker in `selection`.
+    fn toggle_label_in(labels: &[String], selected: usize, selection: &mut Vec<String>) {
+        let Some(label) = labels.get(selected) else {
+            return;
+        };
+
+        if let Some(idx) = selection.iter().position(|name| name == label) {
+            selection.remove(idx);
+        } else {
+            selection.push(label.clone());
+        }
+    }
+
+    pub fn toggle_card_label(&mut self) {
+        App::toggle_label_in(&self.labels, self.selected_label, &mut self.card_labels);
+    }
+
+    pub fn increment_selected_label(&mut self) {
+        if self.selected_label + 1 < self.labels.len() {
+            self.selected_label += 1;
+        }
+    }
+
+    pub fn decrement_selected_label(&mut self) {
+        self.selected_label = self.selected_label.saturating_sub(1);
+    }
+
+    pub fn start_label_filter(&mut self) {
+        self.labels = self
+            .kanban
+            .labels()
+            .iter()
+            .map(|label| label.name.clone())
+            .collect();
+        self.selected_label = 0;
+        self.current_screen = CurrentScreen::LabelFilter;
+    }
+
+    pub fn toggle_label_filter(&mut self) {
+        App::toggle_label_in(&self.labels, self.selected_label, &mut self.label_filter);
+        if !self.is_card_visible(self.selected_column, self.selected_card) {
+            self.step_visible_card(true);
+        }
+    }
+
+    pub fn edit_checklist(&mut self) {
+        self.currently_editing = Some(CurrentlyEditing::Checklist);
+        self.vim = Vim::new(Mode::Normal);
+        self.title_input.set_style(Style::default());
+        self.title_input.set_cursor_style(Style::default());
+        self.description_input.set_style(Style::default());
+        self.description_input.set_cursor_style(Style::default());
+        self.due_input.set_style(Style::default());
+        self.due_input.set_cursor_style(Style::default());
+        self.check_input = TextArea::default();
+        if self.selected_check >= self.checklist.len() {
+            self.selected_check = self.checklist.len().saturating_sub(1);
+        }
+    }
+
+    pub fn edit_check(&mut self, is_new: bool) {
+        self.check_input = if is_new {
+            TextArea::default()
+        } else if let Some(check) = self.checklist.get(self.selected_check) {
+            TextArea::new(vec![check.title.to_string()])
+        } else {
+            return;
+        };
+        self.check_input.set_cursor_line_style(Style::default());
+        self.check_input
+            .set_cursor_style(Style::default().add_modifier(Modifier::REVERSED));
+        self.currently_editing = Some(CurrentlyEditing::Check(is_new));
+        self.vim = Vim::new(Mode::Normal);
+        self.update_vim(Input::from(KeyEvent::from(KeyCode::Char('A'))));
+    }
+
+    pub fn save_check(&mut self, is_new: bool) {
+        let title = self.check_input.lines().concat();
+        if !title.is_empty() {
+            if is_new {
+                self.checklist.push(Check::new(&title, false));
+                self.selected_check = self.checklist.len() - 1;
+            } else if let Some(check) = self.checklist.get_mut(self.selected_check) {
+                check.title = title;
+            }
+        }
+        self.edit_checklist();
+    }
+
+    pub fn toggle_check(&mut self) {
+        if let Some(check) = self.checklist.get_mut(self.selected_check) {
+            check.done = !check.done;
+        }
+    }
+
+    pub fn delete_check(&mut self) {
+        if self.selected_check < self.checklist.len() {
+            self.checklist.remove(self.selected_check);
+        }
+        if self.selected_check >= self.checklist.len() {
+            self.selected_check = self.checklist.len().saturating_sub(1);
+        }
+    }
+
+    pub fn move_check_down(&mut self) {
+        if self.selected_check + 1 < self.checklist.len() {
+            self.checklist
+                .swap(self.selected_check, self.selected_check + 1);
+            self.selected_check += 1;
+        }
+    }
+
+    pub fn move_check_up(&mut self) {
+        if self.selected_check > 0 && self.selected_check < self.checklist.len() {
+            self.checklist
+                .swap(self.selected_check, self.selected_check - 1);
+            self.selected_check -= 1;
+        }
+    }
+
+    pub fn increment_selected_check(&mut self) {
+        if self.selected_check + 1 < self.checklist.len() {
+            self.selected_check += 1;
+        } else {
+            self.selected_check = 0;
+        }
+    }
+
+    pub fn decrement_selected_check(&mut self) {
+        if self.checklist.is_empty() {
+            return;
+        }
+
+        if self.selected_check == 0 {
+            self.selected_check = self.checklist.len() - 1;
+        } else {
+            self.selected_check -= 1;
+        }
     }
 
     pub fn title_value(&self) -> String {
@@ -127,6 +751,14 @@ impl App {
         self.description_input.set_block(block)
     }
 
+    pub fn set_due_block(&mut self, block: Block<'static>) {
+        self.due_input.set_block(block)
+    }
+
+    pub fn set_limit_block(&mut self, block: Block<'static>) {
+        self.limit_input.set_block(block)
+    }
+
     pub fn increment_selected_column(&mut self) {
         if self.kanban.columns().is_empty() {
             return;
@@ -135,13 +767,10 @@ impl App {
         let new = self.selected_column + 1;
         if new < self.kanban.columns().len() {
             self.selected_column = new;
-            if self.selected_column > self.columns_start + self.columns_offset - 1 {
-                self.columns_start += 1;
-            }
         } else {
             self.selected_column = 0;
-            self.columns_start = 0;
         }
+        self.scroll_to_selected_column();
 
         if let Some(column) = self.kanban.columns().get(self.selected_column) {
             if column.cards.is_empty() {
@@ -161,18 +790,10 @@ impl App {
 
         if self.selected_column == 0 {
             self.selected_column = self.kanban.columns().len() - 1;
-            if self.kanban.columns().len() > self.columns_offset {
-                self.columns_start = self.kanban.columns().len() - self.columns_offset;
-            }
         } else {
             self.selected_column -= 1;
-            if self.kanban.columns().len() > self.columns_offset
-                && self.columns_start > 0
-                && self.selected_column == self.columns_start - 1
-            {
-                self.columns_start -= 1;
-            }
         }
+        self.scroll_to_selected_column();
 
         if let Some(column) = self.kanban.columns().get(self.selected_column) {
             if column.cards.is_empty() {
@@ -198,7 +819,7 @@ impl App {
             if self.selected_card == 0 {
                 self.selected_card = column.cards.len() - 1;
             } else {
-                self.selected_card = self.selected_card - 1;
+                self.selected_card -= 1;
             }
         }
     }
@@ -222,20 +843,193 @@ impl App {
         }
     }
 
+    /// Moves the card selection down (or up) one card, skipping cards hidden by the search.
+    pub fn step_visible_card(&mut self, down: bool) {
+        let cards = self
+            .kanban
+            .get_column(self.selected_column)
+            .map_or(0, |column| column.cards.len());
+
+        for _ in 0..cards.max(1) {
+            if down {
+                self.decrement_selected_card();
+            } else {
+                self.increment_selected_card();
+            }
+
+            if self.is_card_visible(self.selected_column, self.selected_card) {
+                break;
+            }
+        }
+    }
+
+    pub fn is_card_visible(&self, column_idx: usize, card_idx: usize) -> bool {
+        let Some(card) = self.kanban.get_card(column_idx, card_idx) else {
+            return true;
+        };
+
+        self.search
+            .as_ref()
+            .is_none_or(|search| search.matches(card))
+            && self.matches_label_filter(card)
+    }
+
+    /// Whether `card` carries every label of the label filter.
+    pub fn matches_label_filter(&self, card: &Card) -> bool {
+        self.label_filter.iter().all(|label| card.has_label(label))
+    }
+
+    pub fn start_search(&mut self) {
+        self.search_input = TextArea::default();
+        self.search_input.set_cursor_line_style(Style::default());
+        self.update_search(false);
+        self.current_screen = CurrentScreen::Search;
+    }
+
+    /// Rebuilds the search from the prompt and moves the selection to the nearest hit.
+    pub fn update_search(&mut self, regex: bool) {
+        self.search = Some(Search::new(&self.search_input.lines().concat(), regex));
+        if !self.is_card_visible(self.selected_column, self.selected_card) {
+            self.next_hit();
+        }
+    }
+
+    pub fn clear_search(&mut self) {
+        self.search = None;
+        self.search_input = TextArea::default();
+    }
+
+    /// Positions of every card matching the search, in board order.
+    pub fn search_hits(&self) -> Vec<(usize, usize)> {
+        let Some(search) = &self.search else {
+            return Vec::new();
+        };
+
+        self.kanban
+            .columns()
+            .iter()
+            .enumerate()
+            .flat_map(|(i, column)| {
+                column
+                    .cards
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, card)| search.matches(card) && self.matches_label_filter(card))
+                    .map(move |(j, _)| (i, j))
+            })
+            .collect()
+    }
+
+    pub fn next_hit(&mut self) {
+        let current = (self.selected_column, self.selected_card);
+        let hits = self.search_hits();
+        if let Some(&(column, card)) = hits.iter().find(|hit| **hit > current).or(hits.first()) {
+            self.select_card(column, card);
+        }
+    }
+
+    pub fn previous_hit(&mut self) {
+        let current = (self.selected_column, self.selected_card);
+        let hits = self.search_hits();
+        if let Some(&(column, card)) = hits
+            .iter()
+            .rev()
+            .find(|hit| **hit < current)
+            .or(hits.last())
+        {
+            self.select_card(column, card);
+        }
+    }
+
+    /// Selects a card anywhere on the board, scrolling its column into view.
+    pub fn select_card(&mut self, column_idx: usize, card_idx: usize) {
+        self.selected_column = column_idx;
+        self.selected_card = card_idx;
+        self.scroll_to_selected_column();
+    }
+
     pub fn update_vim(&mut self, key: Input) {
         if let Some(editing) = &self.currently_editing {
-            let input = if matches!(editing, CurrentlyEditing::Title) {
-                &mut self.title_input
-            } else {
-                &mut self.description_input
+            let input = match editing {
+                CurrentlyEditing::Title => &mut self.title_input,
+                CurrentlyEditing::Description => &mut self.description_input,
+                CurrentlyEditing::Due => &mut self.due_input,
+                CurrentlyEditing::Limit => &mut self.limit_input,
+                CurrentlyEditing::Check(_) => &mut self.check_input,
+                CurrentlyEditing::NewLabel => &mut self.label_input,
+                CurrentlyEditing::Checklist | CurrentlyEditing::Labels => return,
             };
             let vim = Vim::new(self.vim.mode);
-            self.vim = match self.vim.transition(Input::from(key), input) {
+            self.vim = match self.vim.transition(key, input) {
                 Transition::Mode(mode) if vim.mode != mode => Vim::new(mode),
                 Transition::Nop | Transition::Mode(_) => vim,
                 Transition::Pending(input) => vim.with_pending(input),
                 Transition::Quit => vim,
             };
+            input.set_cursor_style(self.vim.mode.cursor_style(&self.config.theme));
         }
     }
 }
+
+/// The modification time and size of the file at `path`, if there is one.
+fn disk_stamp(path: &str) -> Option<(SystemTime, u64)> {
+    let meta = std::fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use super::*;
+
+    /// A fresh directory for one test, emptied first in case an earlier run left it behind.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("crabmat-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn app(dir: &std::path::Path) -> App {
+        let path = dir.join("kanban").to_string_lossy().into_owned();
+        let kanban = Kanban::with_columns("Board", vec![Column::new("Todo", Vec::new())]);
+        App::new(path, kanban, Config::default())
+    }
+
+    #[test]
+    fn records_and_saves_successful_updates() {
+        let dir = temp_dir("app-update");
+        let mut app = app(&dir);
+
+        app.update_kanban(|kanban| kanban.add_card(0, Card::new("A", "")))
+            .unwrap();
+        assert!(app.history.can_undo());
+        let saved = Kanban::from_file(&app.path).unwrap();
+        assert_eq!(saved.columns()[0].cards[0].title, "A");
+
+        app.undo();
+        assert!(app.kanban.columns()[0].cards.is_empty());
+        let saved = Kanban::from_file(&app.path).unwrap();
+        assert!(saved.columns()[0].cards.is_empty());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn rolls_back_failed_updates() {
+        let dir = temp_dir("app-rollback");
+        let mut app = app(&dir);
+        let before = app.kanban.clone();
+
+        let result = app.update_kanban(|kanban| {
+            kanban.set_title("Changed");
+            kanban.add_card(0, Card::new("A", ""))?;
+            kanban.delete_card(5, 0)
+        });
+        assert_eq!(result, Err(KanbanError::ColumnNotFound(5)));
+        assert_eq!(app.kanban, before);
+        assert!(!app.history.can_undo());
+        assert!(!dir.join("kanban").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,242 @@
+use crabmat::Timestamp;
+use crossterm::event::KeyEvent;
+use ratatui::{
+    layout::{Constraint, Layout},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+use tui_textarea::{Input, TextArea};
+
+use crate::{
+    app::{App, CurrentScreen},
+    keymap::{Action, Context},
+    search::Search,
+    ui::centered_rect,
+};
+
+pub fn start(app: &mut App) {
+    app.archive_input = TextArea::default();
+    app.archive_search = None;
+    app.searching_archive = false;
+    app.selected_archived = 0;
+    app.current_screen = CurrentScreen::Archive;
+}
+
+/// An entry of the archive browser, by index into the board's archived columns or cards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Entry {
+    Column(usize),
+    Card(usize),
+}
+
+/// The archived columns and cards matching the search, newest first. A column comes just before
+/// the cards archived with it.
+pub fn hits(app: &App) -> Vec<Entry> {
+    let (columns, archive) = (app.kanban.archived_columns(), app.kanban.archive());
+    let matches = |entry: &Entry| match (&app.archive_search, *entry) {
+        (None, _) => true,
+        (Some(search), Entry::Column(i)) => !search.ranges(&columns[i].column.title).is_empty(),
+        (Some(search), Entry::Card(i)) => {
+            search.matches(&archive[i].card) || !search.ranges(&archive[i].column).is_empty()
+        }
+    };
+
+    let mut hits = (0..archive.len())
+        .map(|i| (archive[i].archived, Entry::Card(i)))
+        .chain((0..columns.len()).map(|i| (columns[i].archived, Entry::Column(i))))
+        .filter(|(_, entry)| matches(entry))
+        .collect::<Vec<_>>();
+    // Stable, so entries archived at the same time keep the newer ones first.
+    hits.reverse();
+    hits.sort_by_key(|(at, _)| std::cmp::Reverse(*at));
+    hits.into_iter().map(|(_, entry)| entry).collect()
+}
+
+/// When an entry was archived, for the browser.
+fn when(at: Option<Timestamp>) -> String {
+    at.map(|at| {
+        let (date, time) = at.local();
+        format!(", {} {}", date, time)
+    })
+    .unwrap_or_default()
+}
+
+pub fn render_archive(f: &mut Frame, app: &App) {
+    let (theme, keymap) = (&app.config.theme, &app.config.keymap);
+    let area = centered_rect(70, 60, f.size());
+    let block = Block::default()
+        .title(format!(
+            "Archive ({})",
+            app.kanban.archive().len() + app.kanban.archived_columns().len()
+        ))
+        .borders(Borders::ALL)
+        .border_style(theme.dialog);
+    let inner = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+
+    let layout = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(1),
+        Constraint::Length(1),
+    ])
+    .split(inner);
+
+    let query = app.archive_input.lines().concat();
+    let search = if app.searching_archive {
+        Line::from(vec![
+            Span::styled("/", theme.prompt),
+            Span::raw(query),
+            Span::styled("█", Style::default().add_modifier(Modifier::SLOW_BLINK)),
+        ])
+    } else if !query.is_empty() {
+        Line::from(vec![Span::styled("/", theme.prompt), Span::raw(query)])
+    } else {
+        let key = keymap.key(Action::SearchArchive);
+        Line::from(Span::styled(format!("{} to search", key), theme.hint))
+    };
+    f.render_widget(Paragraph::new(search), layout[0]);
+
+    let (columns, archive) = (app.kanban.archived_columns(), app.kanban.archive());
+    let mut items = hits(app)
+        .into_iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let style = if i == app.selected_archived {
+                theme.selected_card
+            } else {
+                theme.card
+            };
+            let (title, about) = match entry {
+                Entry::Column(idx) => {
+                    let archived = &columns[idx];
+                    let cards = archive
+                        .iter()
+                        .filter(|card| card.column_id == archived.column.id)
+                        .count();
+                    let about = format!("  column, {} cards{}", cards, when(archived.archived));
+                    (archived.column.title.as_str(), about)
+                }
+                Entry::Card(idx) => {
+                    let archived = &archive[idx];
+                    let about = format!("  from {}{}", archived.column, when(archived.archived));
+                    (archived.card.title.as_str(), about)
+                }
+            };
+            let mut line = match &app.archive_search {
+                Some(search) => search.highlight(title, style, theme.search_match),
+                None => vec![Span::styled(title, style)],
+            };
+            line.push(Span::styled(about, theme.hint));
+            ListItem::new(Line::from(line))
+        })
+        .collect::<Vec<_>>();
+    if items.is_empty() {
+        items.push(ListItem::new(Span::styled(" nothing archived", theme.hint)));
+    }
+
+    let mut state = ListState::default().with_selected(Some(app.selected_archived));
+    f.render_stateful_widget(List::new(items), layout[1], &mut state);
+
+    f.render_widget(
+        Paragraph::new(Span::styled(
+            format!(
+                "{} restore | {} restore to focused column | {} delete for good | {} close",
+                keymap.key(Action::Restore),
+                keymap.key(Action::RestoreHere),
+                keymap.key(Action::Purge),
+                keymap.key(Action::LeaveArchive)
+            ),
+            theme.hint,
+        )),
+        layout[2],
+    );
+}
+
+pub fn events(key: KeyEvent, app: &mut App) {
+    if app.searching_archive {
+        return search_events(key, app);
+    }
+
+    let hits = hits(app);
+    let selected = hits.get(app.selected_archived).copied();
+    match app.config.keymap.action(Context::Archive, key) {
+        Some(action) if action.edits_board() && app.refuse_read_only() => {}
+        Some(Action::ArchiveDown) if app.selected_archived + 1 < hits.len() => {
+            app.selected_archived += 1;
+        }
+        Some(Action::ArchiveUp) => {
+            app.selected_archived = app.selected_archived.saturating_sub(1);
+        }
+        Some(Action::SearchArchive) => app.searching_archive = true,
+        Some(action @ (Action::Restore | Action::RestoreHere)) => {
+            let restored = match selected {
+                // Columns go back at the end of the board, wherever the focus is.
+                Some(Entry::Column(idx)) => app
+                    .update_kanban(|kanban| kanban.restore_column(idx))
+                    .map(|column| (column, 0)),
+                Some(Entry::Card(idx)) => {
+                    let column = match action {
+                        Action::RestoreHere
+                            if app.kanban.get_column(app.selected_column).is_some() =>
+                        {
+                            Some(app.selected_column)
+                        }
+                        _ => None,
+                    };
+                    app.update_kanban(|kanban| kanban.restore_card(idx, column))
+                }
+                None => return,
+            };
+            match restored {
+                Ok((column, card)) => {
+                    app.select_card(column, card);
+                    app.warn_over_limit(column);
+                    clamp(app);
+                }
+                Err(e) => app.message = Some(app.describe_error(e)),
+            }
+        }
+        Some(Action::Purge) => {
+            match selected {
+                Some(Entry::Column(idx)) => {
+                    let _ = app.update_kanban(|kanban| kanban.delete_archived_column(idx));
+                }
+                Some(Entry::Card(idx)) => {
+                    let _ = app.update_kanban(|kanban| kanban.delete_archived(idx));
+                }
+                None => return,
+            }
+            clamp(app);
+        }
+        Some(Action::LeaveArchive) => app.current_screen = CurrentScreen::Main,
+        _ => {}
+    }
+}
+
+fn search_events(key: KeyEvent, app: &mut App) {
+    match app.config.keymap.action(Context::Prompt, key) {
+        Some(Action::Dismiss) => {
+            app.archive_input = TextArea::default();
+            app.archive_search = None;
+            app.searching_archive = false;
+        }
+        Some(Action::Accept) => app.searching_archive = false,
+        _ => {
+            if app.archive_input.input(Input::from(key)) {
+                let query = app.archive_input.lines().concat();
+                app.archive_search = (!query.is_empty()).then(|| Search::new(&query, false));
+                app.selected_archived = 0;
+            }
+        }
+    }
+}
+
+fn clamp(app: &mut App) {
+    let hits = hits(app).len();
+    if app.selected_archived >= hits {
+        app.selected_archived = hits.saturating_sub(1);
+    }
+}
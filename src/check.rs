@@ -1,11 +1,14 @@
 use std::fmt::Display;
 
-use ratatui::text::Line;
-use tui_textarea::TextArea;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
+/// A single checklist item on a `Card`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Check {
     pub title: String,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub done: bool,
 }
 
@@ -25,43 +28,22 @@ impl Check {
     }
 }
 
-impl From<Line<'_>> for Check {
-    fn from(line: Line) -> Self {
-        Check::from(line.to_string())
-    }
-}
-
-impl From<TextArea<'_>> for Check {
-    fn from(input: TextArea<'_>) -> Self {
-        Check::from(input.lines().join("\n"))
-    }
-}
-
-impl From<&TextArea<'_>> for Check {
-    fn from(input: &TextArea<'_>) -> Self {
-        Check::from(input.lines().join("\n"))
-    }
-}
-
 impl From<String> for Check {
     fn from(value: String) -> Self {
-        let mut check = Check::empty();
-        let (done, title) = value.trim_start().split_at(4);
-        check.done = done == "[x] ";
-        check.title = title.to_string();
-
-        check
+        Check::from(&value)
     }
 }
 
 impl From<&String> for Check {
     fn from(value: &String) -> Self {
-        let mut check = Check::empty();
-        let (done, title) = value.trim_start().split_at(4);
-        check.done = done == "[x] ";
-        check.title = title.to_string();
-
-        check
+        let value = value.trim_start();
+        if let Some(title) = value.strip_prefix("[x] ") {
+            Check::new(title, true)
+        } else if let Some(title) = value.strip_prefix("[ ] ") {
+            Check::new(title, false)
+        } else {
+            Check::new(value, false)
+        }
     }
 }
 
@@ -0,0 +1,632 @@
+use std::{error::Error, io::ErrorKind, path::Path, time::UNIX_EPOCH};
+
+use crabmat::{
+    date,
+    diff::{self, Change},
+    io,
+    markdown::{from_markdown, to_markdown},
+    merge, Card, Column, Kanban, KanbanError, LoadError, ParseMode, Timestamp, Urgency,
+};
+
+pub const USAGE: &str = "Usage: crabmat [FILE]
+       crabmat [-f FILE] <COMMAND> [ARGS]
+
+Commands:
+  list [--column COLUMN]            list cards, optionally only those in COLUMN
+  show [CARD]                       print the whole board, or a single card
+  add-column TITLE                  add a column to the end of the board
+  add-card COLUMN TITLE [DESC]      add a card to the end of COLUMN
+  move CARD COLUMN                  move CARD to the end of COLUMN
+  archive CARD                      move CARD to the board's archive
+  delete-card CARD                  delete CARD for good
+  due [--days DAYS]                 list overdue cards and cards due in the next DAYS (7) days
+  export [--format FORMAT]          print the board as FORMAT: json (the default, needs the serde
+                                    feature), markdown (the default without it) or crabmat
+  import SOURCE [--format FORMAT]   replace the board with SOURCE (a file or - for stdin),
+         [--force]                  FORMAT is guessed from the extension when not given
+  restore [N]                       list the board's backups, or put backup N back in place
+  merge BASE OURS THEIRS [-o OUT]   merge two boards changed from BASE into OURS (or OUT, - for
+                                    stdout), failing when conflict cards had to be added
+  diff OLD NEW [--format FORMAT]    list the changes from board OLD to NEW, as text or json
+  help                              print this message
+
+FILE defaults to `kanban`. COLUMN is a column title, 1-based position or ID, CARD is a card
+title or ID. IDs may be written as `#a3f9` (quoted, in most shells) or just `a3f9`.";
+
+const COMMANDS: [&str; 15] = [
+    "list",
+    "show",
+    "add-column",
+    "add-card",
+    "move",
+    "archive",
+    "delete-card",
+    "due",
+    "export",
+    "import",
+    "restore",
+    "merge",
+    "diff",
+    "help",
+    "--help",
+];
+
+pub fn is_command(arg: &str) -> bool {
+    COMMANDS.contains(&arg) || arg == "-h" || arg == "-f" || arg == "--file"
+}
+
+/// Runs a non-interactive command against a board file.
+pub fn run(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let mut args = args.to_vec();
+    let path = take_option(&mut args, &["-f", "--file"])?.unwrap_or("kanban".to_string());
+    let Some((command, args)) = args.split_first() else {
+        return Err(USAGE.into());
+    };
+
+    match command.as_str() {
+        "list" => list(&path, args),
+        "show" => show(&path, args),
+        "add-column" => add_column(&path, args),
+        "add-card" => add_card(&path, args),
+        "move" => move_card(&path, args),
+        "archive" => archive_card(&path, args),
+        "delete-card" => delete_card(&path, args),
+        "due" => due(&path, args),
+        "export" => export(&path, args),
+        "import" => import(&path, args),
+        "restore" => restore(&path, args),
+        "merge" => merge(args),
+        "diff" => diff(args),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        command => Err(format!("unknown command `{}`\n\n{}", command, USAGE).into()),
+    }
+}
+
+/// Removes `--flag value` from `args`, returning the value.
+pub fn take_option(
+    args: &mut Vec<String>,
+    names: &[&str],
+) -> Result<Option<String>, Box<dyn Error>> {
+    let Some(idx) = args.iter().position(|arg| names.contains(&arg.as_str())) else {
+        return Ok(None);
+    };
+
+    if idx + 1 >= args.len() {
+        return Err(format!("{} needs a value", args[idx]).into());
+    }
+
+    let value = args.remove(idx + 1);
+    args.remove(idx);
+    Ok(Some(value))
+}
+
+/// Removes `--flag` from `args`, returning whether it was there.
+pub fn take_flag(args: &mut Vec<String>, names: &[&str]) -> bool {
+    let Some(idx) = args.iter().position(|arg| names.contains(&arg.as_str())) else {
+        return false;
+    };
+
+    args.remove(idx);
+    true
+}
+
+fn expect_args<'a>(
+    args: &'a [String],
+    min: usize,
+    max: usize,
+    usage: &str,
+) -> Result<&'a [String], Box<dyn Error>> {
+    if args.len() < min || args.len() > max {
+        return Err(format!("usage: crabmat {}", usage).into());
+    }
+
+    Ok(args)
+}
+
+/// Loads a board like [`load`], taking an empty file for an empty board.
+fn load_or_empty(path: &str) -> Result<Kanban, Box<dyn Error>> {
+    match Kanban::from_file(path) {
+        Ok(kanban) => Ok(kanban),
+        Err(LoadError::Empty) => Ok(Kanban::new("")),
+        // For the error message.
+        Err(_) => load(path),
+    }
+}
+
+/// Loads a later version of the board `earlier` like [`load_or_empty`], with columns and cards
+/// without an ID keeping the ones they have in `earlier`.
+fn load_like(path: &str, earlier: &Kanban) -> Result<Kanban, Box<dyn Error>> {
+    match Kanban::from_file_like(path, earlier) {
+        Ok(kanban) => Ok(kanban),
+        Err(LoadError::Empty) => Ok(Kanban::new("")),
+        // For the error message.
+        Err(_) => load(path),
+    }
+}
+
+pub fn load(path: &str) -> Result<Kanban, Box<dyn Error>> {
+    match Kanban::from_file(path) {
+        Ok(kanban) => Ok(kanban),
+        Err(LoadError::Io(e)) if e.kind() == ErrorKind::NotFound => {
+            Err(format!("no board at {}", path).into())
+        }
+        Err(e) => Err(format!("could not read {}: {}", path, e).into()),
+    }
+}
+
+pub fn column_idx(kanban: &Kanban, query: &str) -> Result<usize, Box<dyn Error>> {
+    kanban
+        .find_column(query)
+        .ok_or_else(|| format!("no column `{}`", query).into())
+}
+
+pub fn card_idx(kanban: &Kanban, query: &str) -> Result<(usize, usize), Box<dyn Error>> {
+    match kanban.find_cards(query).as_slice() {
+        [] => Err(format!("no card `{}`", query).into()),
+        [found] => Ok(*found),
+        found => {
+            let columns = found
+                .iter()
+                .map(|(column, _)| kanban.columns()[*column].title.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            Err(format!("`{}` matches {} cards (in {})", query, found.len(), columns).into())
+        }
+    }
+}
+
+/// Names the column for errors about it, such as a WIP limit refusing a card.
+fn column_error(kanban: &Kanban, e: KanbanError) -> Box<dyn Error> {
+    match e {
+        KanbanError::ColumnFull(idx) => {
+            let column = &kanban.columns()[idx];
+            format!(
+                "{} is at its WIP limit of {}",
+                column.title,
+                column.limit.unwrap_or_default()
+            )
+            .into()
+        }
+        e => e.into(),
+    }
+}
+
+/// Prints a warning when a column holds more cards than its WIP limit.
+fn warn_over_limit(column: &Column) {
+    if let (true, Some(limit)) = (column.is_over_limit(), column.limit) {
+        eprintln!(
+            "warning: {} is over its WIP limit ({}/{})",
+            column.title,
+            column.cards.len(),
+            limit
+        );
+    }
+}
+
+fn card_line(card: &Card) -> String {
+    let mut line = format!("#{} ", card.id);
+    if let Some(priority) = card.priority {
+        line.push_str(&format!("{} ", priority));
+    }
+    line.push_str(&card.title);
+    for label in &card.labels {
+        line.push_str(&format!(" #{}", label));
+    }
+    if let Some(due) = &card.due {
+        line.push_str(&format!(" (due {})", due));
+    }
+    if !card.checklist.is_empty() {
+        let (done, total) = card.progress();
+        line.push_str(&format!(" {}/{}", done, total));
+    }
+
+    line
+}
+
+/// The column's title, with its card count when it has a WIP limit.
+fn column_heading(column: &Column) -> String {
+    match column.limit {
+        Some(limit) => format!("{} ({}/{})", column.title, column.cards.len(), limit),
+        None => column.title.clone(),
+    }
+}
+
+fn print_column(column: &Column, indent: &str) {
+    for card in &column.cards {
+        println!("{}{}", indent, card_line(card));
+    }
+}
+
+fn print_card(card: &Card, indent: &str) {
+    for line in card.description.lines() {
+        println!("{}{}", indent, line);
+    }
+    for check in &card.checklist {
+        println!("{}{}", indent, check);
+    }
+}
+
+fn list(path: &str, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let mut args = args.to_vec();
+    let column = take_option(&mut args, &["-c", "--column"])?;
+    expect_args(&args, 0, 0, "list [--column COLUMN]")?;
+    let kanban = load(path)?;
+
+    if let Some(column) = column {
+        let idx = column_idx(&kanban, &column)?;
+        print_column(&kanban.columns()[idx], "");
+        return Ok(());
+    }
+
+    for column in kanban.columns() {
+        println!("{}", column_heading(column));
+        print_column(column, "  ");
+    }
+
+    Ok(())
+}
+
+fn show(path: &str, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let args = expect_args(args, 0, 1, "show [CARD]")?;
+    let kanban = load(path)?;
+
+    if let Some(query) = args.first() {
+        let (column, card) = card_idx(&kanban, query)?;
+        let card = &kanban.columns()[column].cards[card];
+        let now = Timestamp::now();
+        println!("{}", card_line(card));
+        match card.entered_column() {
+            Some(entered) => println!(
+                "column: {} (for {})",
+                kanban.columns()[column].title,
+                date::describe_duration(now.0 - entered.0)
+            ),
+            None => println!("column: {}", kanban.columns()[column].title),
+        }
+        if let Some(created) = card.created {
+            println!("created: {}", created);
+        }
+        if let Some(updated) = card.updated {
+            println!("updated: {}", updated);
+        }
+        for transition in &card.history {
+            println!("moved: {} to {}", transition.at, transition.column);
+        }
+        print_card(card, "");
+        return Ok(());
+    }
+
+    println!("{}", kanban.title());
+    for column in kanban.columns() {
+        println!("\n{}", column_heading(column));
+        for card in &column.cards {
+            println!("  {}", card_line(card));
+            print_card(card, "    ");
+        }
+    }
+
+    Ok(())
+}
+
+fn add_column(path: &str, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let args = expect_args(args, 1, 1, "add-column TITLE")?;
+    let mut kanban = load(path)?;
+
+    kanban.add_column(Column::new(&args[0], Vec::new()));
+    kanban.save(path)?;
+    Ok(())
+}
+
+fn add_card(path: &str, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let args = expect_args(args, 2, 3, "add-card COLUMN TITLE [DESC]")?;
+    let mut kanban = load(path)?;
+    let column = column_idx(&kanban, &args[0])?;
+    let description = args.get(2).map(String::as_str).unwrap_or_default();
+
+    kanban
+        .add_card(column, Card::new(&args[1], description))
+        .map_err(|e| column_error(&kanban, e))?;
+    kanban.save(path)?;
+    warn_over_limit(&kanban.columns()[column]);
+    Ok(())
+}
+
+fn move_card(path: &str, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let args = expect_args(args, 2, 2, "move CARD COLUMN")?;
+    let mut kanban = load(path)?;
+    let (column, card) = card_idx(&kanban, &args[0])?;
+    let new_column = column_idx(&kanban, &args[1])?;
+
+    if column != new_column {
+        kanban
+            .move_card(column, new_column, card)
+            .map_err(|e| column_error(&kanban, e))?;
+        kanban.save(path)?;
+        warn_over_limit(&kanban.columns()[new_column]);
+    }
+    Ok(())
+}
+
+fn archive_card(path: &str, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let args = expect_args(args, 1, 1, "archive CARD")?;
+    let mut kanban = load(path)?;
+    let (column, card) = card_idx(&kanban, &args[0])?;
+
+    kanban.archive_card(column, card)?;
+    kanban.save(path)?;
+    Ok(())
+}
+
+fn delete_card(path: &str, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let args = expect_args(args, 1, 1, "delete-card CARD")?;
+    let mut kanban = load(path)?;
+    let (column, card) = card_idx(&kanban, &args[0])?;
+
+    kanban.delete_card(column, card)?;
+    kanban.save(path)?;
+    Ok(())
+}
+
+fn due(path: &str, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let mut args = args.to_vec();
+    let days = match take_option(&mut args, &["--days"])? {
+        Some(days) => days
+            .parse::<i64>()
+            .map_err(|_| format!("--days needs a number, not `{}`", days))?,
+        None => 7,
+    };
+    expect_args(&args, 0, 0, "due [--days DAYS]")?;
+    let kanban = load(path)?;
+    let (today, now) = date::now();
+    let until = today.add_days(days);
+
+    let mut due = kanban
+        .columns()
+        .iter()
+        .flat_map(|column| column.cards.iter().map(move |card| (column, card)))
+        .filter_map(|(column, card)| Some((card.due?, column, card)))
+        .filter(|(due, _, _)| due.date <= until)
+        .collect::<Vec<_>>();
+    due.sort_by_key(|(due, _, _)| *due);
+
+    for (due, column, card) in due {
+        let status = match due.urgency(today, now) {
+            Urgency::Overdue => "overdue",
+            Urgency::Today => "today",
+            Urgency::ThisWeek | Urgency::Later => "",
+        };
+        println!(
+            "{:<16}  {:<7}  {} [{}]",
+            due.to_string(),
+            status,
+            card.title,
+            column.title
+        );
+    }
+
+    Ok(())
+}
+
+fn export(path: &str, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let mut args = args.to_vec();
+    let default = if cfg!(feature = "serde") {
+        "json"
+    } else {
+        "markdown"
+    };
+    let format = take_option(&mut args, &["--format"])?.unwrap_or(default.to_string());
+    expect_args(&args, 0, 0, "export [--format FORMAT]")?;
+    let kanban = load(path)?;
+
+    match format.as_str() {
+        "json" => print!("{}", to_json(&kanban)?),
+        "markdown" | "md" => print!("{}", to_markdown(&kanban)),
+        "crabmat" => print!("{}", kanban),
+        format => return Err(format!("unknown format `{}`", format).into()),
+    }
+
+    Ok(())
+}
+
+fn import(path: &str, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let mut args = args.to_vec();
+    let format = take_option(&mut args, &["--format"])?;
+    let force = take_flag(&mut args, &["--force"]);
+    let args = expect_args(&args, 1, 1, "import SOURCE [--format FORMAT] [--force]")?;
+    let source = &args[0];
+
+    let input = if source == "-" {
+        std::io::read_to_string(std::io::stdin())?
+    } else {
+        std::fs::read_to_string(source)?
+    };
+
+    let format = format.unwrap_or_else(|| match Path::new(source).extension() {
+        Some(ext) if ext == "json" => "json".to_string(),
+        Some(ext) if ext == "md" || ext == "markdown" => "markdown".to_string(),
+        _ if input.trim_start().starts_with('{') => "json".to_string(),
+        _ if input.trim_start().starts_with("# ") => "markdown".to_string(),
+        _ => "crabmat".to_string(),
+    });
+    let kanban = match format.as_str() {
+        "json" => from_json(&input)?,
+        "markdown" | "md" => from_markdown(&input)?,
+        "crabmat" => {
+            let lines = input.lines().map(String::from).collect::<Vec<_>>();
+            Kanban::from_lines(&lines, ParseMode::Strict)?.0
+        }
+        format => return Err(format!("unknown format `{}`", format).into()),
+    };
+
+    if !force && Path::new(path).exists() {
+        return Err(format!("{} already exists, pass --force to replace it", path).into());
+    }
+
+    kanban.save(path)?;
+    Ok(())
+}
+
+fn restore(path: &str, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let args = expect_args(args, 0, 1, "restore [N]")?;
+
+    let Some(n) = args.first() else {
+        let backups = io::backups(path);
+        if backups.is_empty() {
+            println!("no backups of {}", path);
+        }
+        for (n, backup, saved) in backups {
+            let saved = saved
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs() as i64);
+            let (date, time) = Timestamp(saved).local();
+            let summary = match std::fs::read_to_string(&backup) {
+                Ok(text) => {
+                    let lines = text.lines().map(String::from).collect::<Vec<_>>();
+                    match Kanban::from_lines(&lines, ParseMode::Lenient) {
+                        Ok((kanban, _)) => format!(
+                            "{}, {} columns, {} cards",
+                            kanban.title(),
+                            kanban.columns().len(),
+                            kanban
+                                .columns()
+                                .iter()
+                                .map(|c| c.cards.len())
+                                .sum::<usize>()
+                        ),
+                        Err(e) => format!("unreadable: {}", e),
+                    }
+                }
+                Err(e) => format!("unreadable: {}", e),
+            };
+            println!("{}  {} {}  {}", n, date, time, summary);
+        }
+        return Ok(());
+    };
+
+    let n = n
+        .parse::<usize>()
+        .ok()
+        .filter(|n| (1..=io::BACKUPS).contains(n))
+        .ok_or_else(|| format!("backups are numbered 1 to {}", io::BACKUPS))?;
+    match io::restore_backup(path, n) {
+        Ok(kanban) => {
+            println!("restored {} from backup {}", kanban.title(), n);
+            Ok(())
+        }
+        Err(LoadError::Io(e)) if e.kind() == ErrorKind::NotFound => {
+            Err(format!("no backup {} of {}", n, path).into())
+        }
+        Err(e) => Err(format!("could not restore backup {}: {}", n, e).into()),
+    }
+}
+
+/// Merges three versions of a board, the way git hands them to a merge driver: the result replaces
+/// `OURS` and the exit status says whether it is clean.
+fn merge(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let mut args = args.to_vec();
+    let output = take_option(&mut args, &["-o", "--output"])?;
+    let args = expect_args(&args, 3, 3, "merge BASE OURS THEIRS [-o OUT]")?;
+
+    // Git passes an empty base for files added on both sides.
+    let base = load_or_empty(&args[0])?;
+    let ours = load_like(&args[1], &base)?;
+    let theirs = load_like(&args[2], &base)?;
+    let merged = merge::merge(&base, &ours, &theirs);
+
+    match output.as_deref().unwrap_or(&args[1]) {
+        "-" => print!("{}", merged.kanban),
+        output => io::write_atomic(output, &merged.kanban.to_string())?,
+    }
+    if merged.conflicts.is_empty() {
+        return Ok(());
+    }
+    for conflict in &merged.conflicts {
+        eprintln!("  {}", conflict);
+    }
+    Err(format!(
+        "{} conflicts, see the cards labelled `{}`",
+        merged.conflicts.len(),
+        merge::CONFLICT_LABEL
+    )
+    .into())
+}
+
+/// Lists what changed between two versions of a board. Also takes the seven arguments git passes
+/// to an external diff tool, `PATH OLD OLD-HEX OLD-MODE NEW NEW-HEX NEW-MODE`.
+fn diff(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let mut args = args.to_vec();
+    let format = take_option(&mut args, &["--format"])?.unwrap_or("text".to_string());
+    let (old, new) = match args.as_slice() {
+        [old, new] => (old, new),
+        [path, old, _, _, new, _, _] => {
+            println!("diff {}", path);
+            (old, new)
+        }
+        _ => return Err("usage: crabmat diff OLD NEW [--format FORMAT]".into()),
+    };
+
+    // Git passes /dev/null for the missing side of an added or deleted board.
+    let old = load_or_empty(old)?;
+    let changes = diff::diff(&old, &load_like(new, &old)?);
+    match format.as_str() {
+        "text" => {
+            for change in changes {
+                println!("{}", change);
+            }
+        }
+        "json" => print!("{}", changes_to_json(&changes)?),
+        format => return Err(format!("unknown format `{}`", format).into()),
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+fn changes_to_json(changes: &[Change]) -> Result<String, Box<dyn Error>> {
+    Ok(format!("{}\n", serde_json::to_string_pretty(changes)?))
+}
+
+#[cfg(not(feature = "serde"))]
+fn changes_to_json(_changes: &[Change]) -> Result<String, Box<dyn Error>> {
+    Err("JSON support needs crabmat to be built with the `serde` feature".into())
+}
+
+#[cfg(feature = "serde")]
+fn to_json(kanban: &Kanban) -> Result<String, Box<dyn Error>> {
+    Ok(format!("{}\n", serde_json::to_string_pretty(kanban)?))
+}
+
+#[cfg(feature = "serde")]
+fn from_json(input: &str) -> Result<Kanban, Box<dyn Error>> {
+    let mut kanban: Kanban = serde_json::from_str(input)?;
+    kanban.assign_ids();
+
+    // Serde only checks the shape of the input, so read the board back the way it is about to be
+    // saved and refuse anything, such as an impossible date or an ID with spaces, that would not
+    // load again.
+    let lines = kanban
+        .to_string()
+        .lines()
+        .map(String::from)
+        .collect::<Vec<_>>();
+    match Kanban::from_lines(&lines, ParseMode::Strict) {
+        Ok((kanban, _)) => Ok(kanban),
+        Err(LoadError::Parse(e)) => {
+            Err(format!("invalid board: {}: {:?}", e.reason, e.text.trim()).into())
+        }
+        Err(e) => Err(format!("invalid board: {}", e).into()),
+    }
+}
+
+#[cfg(not(feature = "serde"))]
+fn to_json(_kanban: &Kanban) -> Result<String, Box<dyn Error>> {
+    Err("JSON support needs crabmat to be built with the `serde` feature".into())
+}
+
+#[cfg(not(feature = "serde"))]
+fn from_json(_input: &str) -> Result<Kanban, Box<dyn Error>> {
+    Err("JSON support needs crabmat to be built with the `serde` feature".into())
+}
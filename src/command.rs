@@ -0,0 +1,335 @@
+use std::{cmp::Ordering, io::ErrorKind};
+
+use crabmat::{Card, Column, Kanban, LoadError, WipPolicy};
+use crossterm::event::KeyEvent;
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span},
+};
+use tui_textarea::{CursorMove, Input, Key, TextArea};
+
+use crate::{
+    app::{App, CurrentScreen},
+    keymap::{Action, Context},
+};
+
+const COMMANDS: [&str; 14] = [
+    "w",
+    "q",
+    "wq",
+    "x",
+    "e",
+    "move",
+    "sort",
+    "rename",
+    "new",
+    "wip-policy",
+    "undo",
+    "redo",
+    "lock",
+    "help",
+];
+
+const HELP: &str = ":w [file] | :q | :wq | :e file | :move column | \
+:sort [title|priority|due|created] [reverse] | :rename [col] title | :new col|card title | \
+:wip-policy [warn|refuse] | :undo | :redo | :lock";
+
+const SORT_KEYS: [&str; 5] = ["title", "priority", "due", "created", "reverse"];
+
+/// Tab completion candidates for the word being typed, and which one is shown.
+pub struct Completion {
+    prefix: String,
+    candidates: Vec<String>,
+    idx: usize,
+}
+
+/// The footer line for the command prompt.
+pub fn footer_line(app: &App) -> Option<Line<'static>> {
+    if !matches!(app.current_screen, CurrentScreen::Command) {
+        return None;
+    }
+
+    Some(Line::from(vec![
+        Span::styled(":", app.config.theme.prompt),
+        Span::raw(app.command_input.lines().concat()),
+        Span::styled("█", Style::default().add_modifier(Modifier::SLOW_BLINK)),
+    ]))
+}
+
+pub fn events(key: KeyEvent, app: &mut App) {
+    match app.config.keymap.action(Context::Prompt, key) {
+        Some(Action::Dismiss) => stop(app),
+        Some(Action::Accept) => {
+            let line = app.command_input.lines().concat();
+            stop(app);
+            if !line.trim().is_empty() {
+                app.command_history.retain(|previous| previous != &line);
+                app.command_history.push(line.clone());
+                if let Err(message) = execute(app, &line) {
+                    app.message = Some(message);
+                }
+            }
+        }
+        Some(Action::Complete) => complete(app),
+        Some(Action::OlderCommand) => browse_history(app, true),
+        Some(Action::NewerCommand) => browse_history(app, false),
+        _ => match Input::from(key) {
+            Input {
+                key: Key::Backspace,
+                ..
+            } if app.command_input.is_empty() => stop(app),
+            input => {
+                app.command_input.input(input);
+                app.completion = None;
+            }
+        },
+    }
+}
+
+pub fn start(app: &mut App) {
+    app.command_input = TextArea::default();
+    app.command_history_idx = None;
+    app.completion = None;
+    app.message = None;
+    app.current_screen = CurrentScreen::Command;
+}
+
+fn stop(app: &mut App) {
+    app.command_input = TextArea::default();
+    app.command_history_idx = None;
+    app.completion = None;
+    app.current_screen = CurrentScreen::Main;
+}
+
+fn set_input(app: &mut App, line: &str) {
+    app.command_input = TextArea::new(vec![line.to_string()]);
+    app.command_input.move_cursor(CursorMove::End);
+}
+
+fn browse_history(app: &mut App, older: bool) {
+    if app.command_history.is_empty() {
+        return;
+    }
+
+    let last = app.command_history.len() - 1;
+    app.command_history_idx = match (app.command_history_idx, older) {
+        (None, true) => Some(last),
+        (None, false) => None,
+        (Some(idx), true) => Some(idx.saturating_sub(1)),
+        (Some(idx), false) if idx < last => Some(idx + 1),
+        (Some(_), false) => None,
+    };
+
+    let line = match app.command_history_idx {
+        Some(idx) => app.command_history[idx].clone(),
+        None => String::new(),
+    };
+    set_input(app, &line);
+}
+
+/// Completes command names, column titles after `:move` and the arguments of `:new`, `:sort` and
+/// `:wip-policy`.
+/// Repeated presses cycle through the candidates.
+fn complete(app: &mut App) {
+    if let Some(completion) = app.completion.as_mut() {
+        if completion.candidates.is_empty() {
+            return;
+        }
+        completion.idx = (completion.idx + 1) % completion.candidates.len();
+        let line = format!(
+            "{}{}",
+            completion.prefix, completion.candidates[completion.idx]
+        );
+        set_input(app, &line);
+        return;
+    }
+
+    let line = app.command_input.lines().concat();
+    let (prefix, word, candidates) = match line.split_once(' ') {
+        None => (
+            String::new(),
+            line.as_str(),
+            COMMANDS.iter().map(|command| command.to_string()).collect(),
+        ),
+        Some(("move", word)) => (
+            "move ".to_string(),
+            word,
+            app.kanban
+                .columns()
+                .iter()
+                .map(|column| column.title.clone())
+                .collect(),
+        ),
+        Some(("new", word)) => ("new ".to_string(), word, vec!["col".into(), "card".into()]),
+        Some(("wip-policy", word)) => (
+            "wip-policy ".to_string(),
+            word,
+            vec!["warn".into(), "refuse".into()],
+        ),
+        Some(("sort", word)) => match word.split_once(' ') {
+            Some((key, word)) => (format!("sort {} ", key), word, vec!["reverse".to_string()]),
+            None => (
+                "sort ".to_string(),
+                word,
+                SORT_KEYS.iter().map(|key| key.to_string()).collect(),
+            ),
+        },
+        Some(_) => return,
+    };
+
+    let lower = word.to_lowercase();
+    let candidates = candidates
+        .into_iter()
+        .filter(|candidate: &String| candidate.to_lowercase().starts_with(&lower))
+        .collect::<Vec<_>>();
+    if let Some(first) = candidates.first() {
+        set_input(app, &format!("{}{}", prefix, first));
+    }
+    app.completion = Some(Completion {
+        prefix,
+        candidates,
+        idx: 0,
+    });
+}
+
+/// Runs a command line, returning a message to show in the footer on failure.
+pub fn execute(app: &mut App, line: &str) -> Result<(), String> {
+    let line = line.trim();
+    let (command, arg) = match line.split_once(' ') {
+        Some((command, arg)) => (command, arg.trim()),
+        None => (line, ""),
+    };
+    let (column, card) = (app.selected_column, app.selected_card);
+
+    let edits = match command {
+        "w" | "wq" | "x" => arg.is_empty(),
+        "wip-policy" => !arg.is_empty(),
+        "move" | "sort" | "rename" | "new" | "undo" | "redo" => true,
+        _ => false,
+    };
+    if edits && app.refuse_read_only() {
+        return Ok(());
+    }
+
+    match command {
+        "w" if arg.is_empty() => app.save_kanban(),
+        "w" => app.kanban.save(arg).map_err(|e| e.to_string())?,
+        // A held back save is answered in the conflict prompt before leaving.
+        "q" => app.quit = !app.conflict,
+        "wq" | "x" => {
+            app.save_kanban();
+            app.quit = !app.conflict;
+        }
+        "e" if arg.is_empty() => return Err("usage: :e file".to_string()),
+        // Opening another board would drop the held back edits without asking.
+        "e" if app.conflict => app.current_screen = CurrentScreen::Conflict,
+        "e" => open(app, arg)?,
+        "lock" => app.take_lock(true)?,
+        "move" => {
+            let new_column = app
+                .kanban
+                .find_column(arg)
+                .ok_or(format!("no column `{}`", arg))?;
+            if new_column != column && app.move_card(column, new_column, card) {
+                app.select_card(new_column, app.kanban.columns()[new_column].cards.len() - 1);
+            }
+        }
+        "sort" => {
+            let mut words = arg.split_whitespace().collect::<Vec<_>>();
+            let reverse = words.last() == Some(&"reverse");
+            if reverse {
+                words.pop();
+            }
+            let key = match words.as_slice() {
+                [] => "title",
+                [key] => key,
+                _ => return Err("usage: :sort [title|priority|due|created] [reverse]".to_string()),
+            };
+            let compare: fn(&Card, &Card) -> Ordering = match key {
+                "title" => |a, b| a.title.to_lowercase().cmp(&b.title.to_lowercase()),
+                // Cards without a priority or due date go last, those without a creation time
+                // (older than the field) first.
+                "priority" => |a, b| compare_options(a.priority, b.priority),
+                "due" => |a, b| compare_options(a.due, b.due),
+                "created" => |a, b| a.created.cmp(&b.created),
+                key => return Err(format!("cannot sort by `{}`", key)),
+            };
+            app.update_kanban(|kanban| {
+                kanban.sort_cards_by(column, |a, b| {
+                    let order = compare(a, b);
+                    if reverse {
+                        order.reverse()
+                    } else {
+                        order
+                    }
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        }
+        "rename" => match arg.split_once(' ') {
+            Some(("col", title)) => app
+                .update_kanban(|kanban| kanban.set_col_title(column, title.trim()))
+                .map_err(|e| e.to_string())?,
+            _ if arg.is_empty() => return Err("usage: :rename [col] title".to_string()),
+            _ => app
+                .update_kanban(|kanban| kanban.set_card_title(column, card, arg))
+                .map_err(|e| e.to_string())?,
+        },
+        "new" => match arg.split_once(' ') {
+            Some(("col", title)) => {
+                let new_column = Column::new(title.trim(), Vec::new());
+                let _ = app.update_kanban(|kanban| {
+                    kanban.add_column(new_column);
+                    Ok(())
+                });
+            }
+            Some(("card", title)) => {
+                app.update_kanban(|kanban| kanban.add_card(column, Card::new(title.trim(), "")))
+                    .map_err(|e| app.describe_error(e))?;
+                app.warn_over_limit(column);
+            }
+            _ => return Err("usage: :new col|card title".to_string()),
+        },
+        "wip-policy" if arg.is_empty() => {
+            app.message = Some(format!("wip-policy {}", app.kanban.wip_policy()));
+        }
+        "wip-policy" => {
+            let policy = arg
+                .parse::<WipPolicy>()
+                .map_err(|_| "usage: :wip-policy [warn|refuse]".to_string())?;
+            app.update_kanban(|kanban| {
+                kanban.set_wip_policy(policy);
+                Ok(())
+            })
+            .map_err(|e| e.to_string())?;
+        }
+        "undo" => app.undo(),
+        "redo" => app.redo(),
+        "help" => app.message = Some(HELP.to_string()),
+        command => return Err(format!("not a command: {}", command)),
+    }
+
+    Ok(())
+}
+
+/// Orders `Some` values before `None`.
+fn compare_options<T: Ord>(a: Option<T>, b: Option<T>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Switches the app to another board file, creating an empty board when it does not exist.
+fn open(app: &mut App, path: &str) -> Result<(), String> {
+    let kanban = match Kanban::from_file(path) {
+        Ok(kanban) => kanban,
+        Err(LoadError::Io(e)) if e.kind() == ErrorKind::NotFound => Kanban::new(path),
+        Err(e) => return Err(format!("could not open {}: {}", path, e)),
+    };
+
+    app.open(path, kanban);
+    Ok(())
+}
@@ -0,0 +1,265 @@
+use std::{collections::HashMap, env, fs, io::ErrorKind, path::PathBuf};
+
+use toml::{Table, Value};
+
+use crate::{
+    keymap::{Action, KeyChord, Keymap},
+    theme::Theme,
+};
+
+/// User settings read from `$XDG_CONFIG_HOME/crabmat/config.toml`.
+pub struct Config {
+    pub keymap: Keymap,
+    pub theme: Theme,
+    pub columns: ColumnLayout,
+    /// Whether card and column IDs are drawn next to their titles.
+    pub show_ids: bool,
+}
+
+/// How the board is split into columns.
+pub struct ColumnLayout {
+    /// The narrowest a column may get before fewer columns are shown at once.
+    pub min_width: u16,
+    /// Relative widths by column title, 1 for columns not listed.
+    pub weights: HashMap<String, u16>,
+}
+
+impl Default for ColumnLayout {
+    fn default() -> Self {
+        Self {
+            min_width: 24,
+            weights: HashMap::new(),
+        }
+    }
+}
+
+impl ColumnLayout {
+    /// How many of the columns with these weights, from the first, fit side by side in `width`
+    /// cells without the narrowest one getting below `min-width`. At least one.
+    pub fn visible(&self, width: u16, weights: &[u16]) -> usize {
+        let (mut total, mut narrowest) = (0u32, u32::MAX);
+        let mut count = 0;
+        for &weight in weights {
+            total += u32::from(weight);
+            narrowest = narrowest.min(u32::from(weight));
+            // Each column gets its share of the width by weight.
+            if u32::from(width) * narrowest < u32::from(self.min_width.max(1)) * total {
+                break;
+            }
+            count += 1;
+        }
+
+        count.max(1)
+    }
+
+    pub fn weight(&self, title: &str) -> u16 {
+        self.weights.get(title).copied().unwrap_or(1)
+    }
+
+    /// Reads `min-width` and the `[columns.weights]` table.
+    fn parse(&mut self, table: Table) -> Result<(), String> {
+        for (key, value) in table {
+            match (key.as_str(), value) {
+                ("min-width", Value::Integer(width)) if (1..=u16::MAX.into()).contains(&width) => {
+                    self.min_width = width as u16;
+                }
+                ("min-width", _) => return Err("min-width must be a positive number".to_string()),
+                ("weights", Value::Table(weights)) => {
+                    for (title, weight) in weights {
+                        let weight = weight
+                            .as_integer()
+                            .and_then(|weight| u16::try_from(weight).ok())
+                            .filter(|weight| *weight > 0)
+                            .ok_or_else(|| {
+                                format!("weight for `{}` must be a positive number", title)
+                            })?;
+                        self.weights.insert(title, weight);
+                    }
+                }
+                ("weights", _) => return Err("weights must be a table".to_string()),
+                (key, _) => return Err(format!("unknown columns setting `{}`", key)),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            keymap: Keymap::default(),
+            theme: Theme::from_env(),
+            columns: ColumnLayout::default(),
+            show_ids: false,
+        }
+    }
+}
+
+impl Config {
+    /// Where the config file lives, falling back to `~/.config` when `XDG_CONFIG_HOME` is unset.
+    pub fn path() -> Option<PathBuf> {
+        let dir = match env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+        };
+
+        Some(dir.join("crabmat").join("config.toml"))
+    }
+
+    /// Loads the config file, using the defaults when there is none.
+    pub fn load() -> Result<Self, String> {
+        let Some(path) = Config::path() else {
+            return Ok(Config::default());
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(text) => Config::parse(&text).map_err(|e| format!("{}: {}", path.display(), e)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(format!("{}: {}", path.display(), e)),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let table = text.parse::<Table>().map_err(|e| e.to_string())?;
+        let mut config = Config::default();
+
+        for (section, value) in table {
+            match (section.as_str(), value) {
+                ("keys", Value::Table(keys)) => config.parse_keys(keys)?,
+                ("theme", Value::Table(theme)) => config.theme.parse(theme)?,
+                ("columns", Value::Table(columns)) => config.columns.parse(columns)?,
+                ("display", Value::Table(display)) => config.parse_display(display)?,
+                (section, _) => return Err(format!("unknown section `{}`", section)),
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Reads the `[display]` section.
+    fn parse_display(&mut self, display: Table) -> Result<(), String> {
+        for (key, value) in display {
+            match (key.as_str(), value) {
+                ("show-ids", Value::Boolean(show)) => self.show_ids = show,
+                ("show-ids", _) => return Err("show-ids must be true or false".to_string()),
+                (key, _) => return Err(format!("unknown display setting `{}`", key)),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads `action = "key"` or `action = ["key", ...]` pairs from the `[keys]` section.
+    fn parse_keys(&mut self, keys: Table) -> Result<(), String> {
+        let mut bindings = Vec::new();
+        for (name, value) in keys {
+            let action = name.parse::<Action>()?;
+            let chords = match value {
+                Value::String(key) => vec![key],
+                Value::Array(keys) => keys
+                    .into_iter()
+                    .map(|key| match key {
+                        Value::String(key) => Ok(key),
+                        _ => Err(format!("keys for `{}` must be strings", name)),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+                _ => return Err(format!("keys for `{}` must be a string or a list", name)),
+            }
+            .iter()
+            .map(|key| key.parse::<KeyChord>())
+            .collect::<Result<Vec<_>, _>>()?;
+
+            bindings.push((action, chords));
+        }
+
+        self.keymap.bind(bindings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use super::*;
+    use crate::keymap::Context;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn configured_keys_win_over_defaults() {
+        let config = Config::parse("[keys]\ndelete-card = \"x\"\nundo = \"d\"").unwrap();
+        let keymap = &config.keymap;
+        assert_eq!(
+            keymap.action(Context::Main, key(KeyCode::Char('x'))),
+            Some(Action::DeleteCard)
+        );
+        assert_eq!(
+            keymap.action(Context::Main, key(KeyCode::Char('d'))),
+            Some(Action::Undo)
+        );
+        assert_eq!(keymap.action(Context::Main, key(KeyCode::Char('u'))), None);
+        // Other contexts keep their own use of the key.
+        assert_eq!(
+            keymap.action(Context::Checklist, key(KeyCode::Char('d'))),
+            Some(Action::DeleteCheck)
+        );
+    }
+
+    #[test]
+    fn configured_editor_keys_win_in_the_panes_falling_back_to_it() {
+        let config = Config::parse("[keys]\nsave = \"x\"").unwrap();
+        let keymap = &config.keymap;
+        assert_eq!(
+            keymap.action(Context::Checklist, key(KeyCode::Char('x'))),
+            Some(Action::Save)
+        );
+        assert_eq!(
+            keymap.action(Context::Checklist, key(KeyCode::Char(' '))),
+            Some(Action::ToggleCheck)
+        );
+    }
+
+    #[test]
+    fn rejects_configured_keys_that_clash() {
+        let error = Config::parse("[keys]\nredo = \"y\"\nundo = [\"u\", \"y\"]")
+            .err()
+            .unwrap();
+        assert!(error.contains("`y` is bound to both"), "{}", error);
+
+        // The same key on different screens is fine.
+        assert!(Config::parse("[keys]\nredo = \"y\"\nconfirm = \"y\"").is_ok());
+    }
+
+    #[test]
+    fn fits_fewer_columns_when_some_are_wider() {
+        let layout = ColumnLayout::default();
+        assert_eq!(layout.visible(100, &[1, 1, 1, 1, 1]), 4);
+        // Three columns with one twice as wide leave 25 cells for the others, four leave 20.
+        assert_eq!(layout.visible(100, &[2, 1, 1, 1]), 3);
+        assert_eq!(layout.visible(100, &[1, 1, 1, 2]), 3);
+        assert_eq!(layout.visible(100, &[4, 1]), 1);
+        assert_eq!(layout.visible(10, &[1, 1]), 1);
+        assert_eq!(layout.visible(100, &[]), 1);
+    }
+
+    #[test]
+    fn writes_keys_as_they_are_read() {
+        for text in ["x", "C-r", "A-Enter", "Space", "S-Tab", "C-A-PageDown", "-"] {
+            let chord = text.parse::<KeyChord>().unwrap();
+            assert_eq!(chord.to_string(), text);
+        }
+    }
+
+    #[test]
+    fn binds_shift_tab_to_the_key_terminals_send() {
+        let config = Config::parse("[keys]\nfocus-left = \"S-Tab\"").unwrap();
+        for modifiers in [KeyModifiers::SHIFT, KeyModifiers::NONE] {
+            let key = KeyEvent::new(KeyCode::BackTab, modifiers);
+            let action = config.keymap.action(Context::Main, key);
+            assert_eq!(action, Some(Action::FocusLeft));
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use crossterm::event::KeyEvent;
+use ratatui::{
+    layout::Alignment,
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::{
+    app::App,
+    keymap::{Action, Context},
+    ui::{centered_rect, key_hint},
+};
+
+/// The prompt shown when the board file changed on disk while there were changes here to save.
+pub fn render_conflict(f: &mut Frame, app: &mut App) {
+    let theme = &app.config.theme;
+    let block = Block::default()
+        .title("Board changed on disk")
+        .borders(Borders::ALL)
+        .border_style(theme.danger)
+        .style(theme.dialog);
+
+    let lines = vec![
+        Line::from(format!(
+            "{} was changed by something else since it was last saved here.",
+            app.path
+        )),
+        Line::from(""),
+        Line::from(
+            [
+                key_hint(app, Action::KeepMine, "keep mine, overwriting the file"),
+                vec![Span::raw(" | ")],
+                key_hint(app, Action::TakeTheirs, "take theirs"),
+                vec![Span::raw(format!(
+                    ", {} brings mine back",
+                    app.config.keymap.key(Action::Undo)
+                ))],
+            ]
+            .concat(),
+        ),
+    ];
+    let text = Paragraph::new(Text::from(lines))
+        .block(block)
+        .wrap(Wrap { trim: true })
+        .alignment(Alignment::Center);
+
+    let area = centered_rect(50, 25, f.size());
+    f.render_widget(Clear, area);
+    f.render_widget(text, area);
+}
+
+pub fn events(key: KeyEvent, app: &mut App) {
+    match app.config.keymap.action(Context::Conflict, key) {
+        Some(Action::KeepMine) => app.keep_mine(),
+        Some(Action::TakeTheirs) => app.take_theirs(),
+        _ => {}
+    }
+}
@@ -0,0 +1,345 @@
+use std::{fmt::Display, str::FromStr};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A calendar date, without a time zone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Date {
+    year: i32,
+    month: u8,
+    day: u8,
+}
+
+/// A time of day, to the minute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Time {
+    hour: u8,
+    minute: u8,
+}
+
+/// When a card is due: a date and, optionally, a time on that date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Due {
+    pub date: Date,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub time: Option<Time>,
+}
+
+/// How close a due date is, relative to now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Urgency {
+    Overdue,
+    Today,
+    ThisWeek,
+    Later,
+}
+
+fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+impl Date {
+    /// Returns `None` when the day does not exist.
+    pub fn new(year: i32, month: u8, day: u8) -> Option<Self> {
+        if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+            return None;
+        }
+
+        Some(Self { year, month, day })
+    }
+
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    /// Days since 1970-01-01.
+    pub fn days(&self) -> i64 {
+        // Howard Hinnant's days_from_civil.
+        let year = i64::from(self.year) - i64::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let month = i64::from(self.month);
+        let day_of_year =
+            (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(self.day) - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146097 + day_of_era - 719468
+    }
+
+    /// The date `days` days after 1970-01-01.
+    pub fn from_days(days: i64) -> Self {
+        // Howard Hinnant's civil_from_days.
+        let days = days + 719468;
+        let era = days.div_euclid(146097);
+        let day_of_era = days - era * 146097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u8;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+        Self {
+            year: year as i32,
+            month,
+            day,
+        }
+    }
+
+    pub fn add_days(&self, days: i64) -> Self {
+        Date::from_days(self.days() + days)
+    }
+}
+
+impl Time {
+    /// Returns `None` when the time does not exist.
+    pub fn new(hour: u8, minute: u8) -> Option<Self> {
+        if hour > 23 || minute > 59 {
+            return None;
+        }
+
+        Some(Self { hour, minute })
+    }
+
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    pub fn minute(&self) -> u8 {
+        self.minute
+    }
+}
+
+impl Due {
+    pub fn new(date: Date, time: Option<Time>) -> Self {
+        Self { date, time }
+    }
+
+    /// How urgent the due date is at `today`, `time`. A card due today without a time only
+    /// becomes overdue tomorrow; "this week" means within the next seven days.
+    pub fn urgency(&self, today: Date, time: Time) -> Urgency {
+        let days = self.date.days() - today.days();
+        match days {
+            ..=-1 => Urgency::Overdue,
+            0 if self.time.is_some_and(|due| due < time) => Urgency::Overdue,
+            0 => Urgency::Today,
+            1..=6 => Urgency::ThisWeek,
+            _ => Urgency::Later,
+        }
+    }
+}
+
+/// A point in time, in whole seconds since 1970-01-01 00:00 UTC. Written as
+/// `YYYY-MM-DDTHH:MM:SSZ`, in board files and JSON alike.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp(pub i64);
+
+#[cfg(feature = "serde")]
+impl Serialize for Timestamp {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(|_| {
+            serde::de::Error::custom(format!(
+                "invalid timestamp `{}`, expected YYYY-MM-DDTHH:MM:SSZ",
+                text
+            ))
+        })
+    }
+}
+
+impl Timestamp {
+    pub fn now() -> Self {
+        let seconds = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs() as i64);
+        Self(seconds)
+    }
+
+    /// The date and time in UTC.
+    pub fn utc(&self) -> (Date, Time) {
+        let minutes = self.0.rem_euclid(86400) / 60;
+        (
+            Date::from_days(self.0.div_euclid(86400)),
+            Time {
+                hour: (minutes / 60) as u8,
+                minute: (minutes % 60) as u8,
+            },
+        )
+    }
+
+    /// The date and time in the local time zone.
+    #[cfg(unix)]
+    pub fn local(&self) -> (Date, Time) {
+        let seconds = self.0 as libc::time_t;
+        // SAFETY: `localtime_r` only writes to the `tm` it is given.
+        let tm = unsafe {
+            let mut tm = std::mem::zeroed::<libc::tm>();
+            if libc::localtime_r(&seconds, &mut tm).is_null() {
+                return self.utc();
+            }
+            tm
+        };
+
+        match (
+            Date::new(tm.tm_year + 1900, (tm.tm_mon + 1) as u8, tm.tm_mday as u8),
+            Time::new(tm.tm_hour as u8, tm.tm_min as u8),
+        ) {
+            (Some(date), Some(time)) => (date, time),
+            _ => self.utc(),
+        }
+    }
+
+    /// The date and time in UTC, where the local time zone is unknown.
+    #[cfg(not(unix))]
+    pub fn local(&self) -> (Date, Time) {
+        self.utc()
+    }
+}
+
+/// The current local date and time.
+pub fn now() -> (Date, Time) {
+    Timestamp::now().local()
+}
+
+/// A rough, human readable length of time such as `3d 4h`, `2h 15m` or `5m`, using the two
+/// largest units.
+pub fn describe_duration(seconds: i64) -> String {
+    let minutes = seconds.max(0) / 60;
+    let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+    match (days, hours) {
+        (0, 0) => format!("{}m", minutes),
+        (0, hours) => format!("{}h {}m", hours, minutes),
+        (days, hours) => format!("{}d {}h", days, hours),
+    }
+}
+
+impl Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl Display for Time {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:02}:{:02}", self.hour, self.minute)
+    }
+}
+
+impl Display for Due {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.time {
+            Some(time) => write!(f, "{} {}", self.date, time),
+            None => write!(f, "{}", self.date),
+        }
+    }
+}
+
+impl FromStr for Date {
+    type Err = ();
+
+    /// Parses `YYYY-MM-DD`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(3, '-');
+        let (Some(year), Some(month), Some(day)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(());
+        };
+        Date::new(
+            year.parse().map_err(|_| ())?,
+            month.parse().map_err(|_| ())?,
+            day.parse().map_err(|_| ())?,
+        )
+        .ok_or(())
+    }
+}
+
+impl FromStr for Time {
+    type Err = ();
+
+    /// Parses `HH:MM`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (hour, minute) = s.split_once(':').ok_or(())?;
+        Time::new(
+            hour.parse().map_err(|_| ())?,
+            minute.parse().map_err(|_| ())?,
+        )
+        .ok_or(())
+    }
+}
+
+impl FromStr for Due {
+    type Err = ();
+
+    /// Parses `YYYY-MM-DD` or `YYYY-MM-DD HH:MM`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (date, time) = match s.split_once(' ') {
+            Some((date, time)) => (date, Some(time.trim().parse()?)),
+            None => (s, None),
+        };
+
+        Ok(Due::new(date.parse()?, time))
+    }
+}
+
+impl Display for Timestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (date, time) = self.utc();
+        write!(f, "{}T{}:{:02}Z", date, time, self.0.rem_euclid(60))
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = ();
+
+    /// Parses `YYYY-MM-DDTHH:MM:SSZ`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (date, time) = s.trim().split_once('T').ok_or(())?;
+        let time = time.strip_suffix('Z').ok_or(())?;
+        let (time, seconds) = time.rsplit_once(':').ok_or(())?;
+        let date = date.parse::<Date>()?;
+        let time = time.parse::<Time>()?;
+        let seconds = seconds.parse::<i64>().map_err(|_| ())?;
+        if !(0..60).contains(&seconds) {
+            return Err(());
+        }
+
+        Ok(Timestamp(
+            date.days() * 86400
+                + i64::from(time.hour) * 3600
+                + i64::from(time.minute) * 60
+                + seconds,
+        ))
+    }
+}
@@ -1,33 +1,34 @@
+use crate::{
+    app::{App, CurrentScreen},
+    keymap::{Action, Context},
+    ui::{centered_rect, key_hint},
+};
+use crabmat::Card;
 use crossterm::event::KeyEvent;
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout},
-    style::{Color, Modifier, Style},
+    style::Style,
     text::{Line, Span, Text},
     widgets::{Block, Borders, Clear, Paragraph, Wrap},
     Frame,
 };
-use tui_textarea::{Input, Key};
-
-use crate::{
-    app::{App, CurrentScreen},
-    kanban::Card,
-    ui::centered_rect,
-};
 
-pub fn render_delete_card(f: &mut Frame, _app: &mut App, card: Card) {
+pub fn render_delete_card(f: &mut Frame, app: &mut App, card: Card) {
     let popup_block = Block::default()
         .borders(Borders::ALL)
-        .style(Style::default());
+        .border_style(app.config.theme.danger);
 
     let lines = vec![
-        Line::from(Span::styled("Delete", Style::default())),
+        Line::from(Span::styled("Archive", app.config.theme.danger)),
         Line::from(Span::styled(card.title, Style::default())),
-        Line::from(vec![
-            Span::styled("y", Style::default().add_modifier(Modifier::UNDERLINED)),
-            Span::styled("es | ", Style::default()),
-            Span::styled("n", Style::default().add_modifier(Modifier::UNDERLINED)),
-            Span::styled("o", Style::default()),
-        ]),
+        Line::from(
+            [
+                key_hint(app, Action::Confirm, "yes"),
+                vec![Span::raw(" | ")],
+                key_hint(app, Action::Deny, "no"),
+            ]
+            .concat(),
+        ),
     ];
     let text = Paragraph::new(Text::from(lines))
         .wrap(Wrap { trim: true })
@@ -43,7 +44,7 @@ pub fn render_delete_card(f: &mut Frame, _app: &mut App, card: Card) {
         .constraints([Constraint::Percentage(100)])
         .split(area);
 
-    let style = Style::default().fg(Color::White);
+    let style = app.config.theme.dialog;
 
     let left_block = Block::default().borders(Borders::NONE).style(style);
     let left_layout = Layout::default()
@@ -57,30 +58,14 @@ pub fn render_delete_card(f: &mut Frame, _app: &mut App, card: Card) {
 }
 
 pub fn events(key: KeyEvent, app: &mut App) {
-    match Input::from(key) {
-        Input {
-            key: Key::Char('s') | Key::Enter,
-            ..
-        } if app.vim.is_normal() => {
-            app.save_kanban();
-            app.current_screen = CurrentScreen::Main;
-        }
-        Input {
-            key: Key::Char('y'),
-            ..
-        } => {
-            app.kanban
-                .delete_card(app.selected_column, app.selected_card);
-            app.save_kanban();
+    match app.config.keymap.action(Context::Dialog, key) {
+        Some(Action::Confirm) => {
+            let (column, card) = (app.selected_column, app.selected_card);
+            let _ = app.update_kanban(|kanban| kanban.archive_card(column, card));
             app.decrement_selected_card();
             app.current_screen = CurrentScreen::Main;
         }
-        Input {
-            key: Key::Char('q') |Key::Char('n') | Key::Esc,
-            ..
-        } if app.vim.is_normal() => app.stop_edit(),
-        _ => {
-            app.update_vim(Input::from(key));
-        }
+        Some(Action::Deny) => app.stop_edit(),
+        _ => {}
     }
 }
@@ -0,0 +1,119 @@
+use crabmat::{date::describe_duration, Card, Timestamp};
+use crossterm::event::KeyEvent;
+use ratatui::{
+    style::Modifier,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::{
+    app::{App, CurrentScreen},
+    keymap::{Action, Context},
+    ui::centered_rect,
+};
+
+fn local(timestamp: Timestamp) -> String {
+    let (date, time) = timestamp.local();
+    format!("{} {}", date, time)
+}
+
+/// The card's creation and moves, oldest first, each with how long the card stayed.
+fn timeline(card: &Card, now: Timestamp) -> Vec<(Timestamp, String, i64)> {
+    let mut entries = Vec::new();
+    if let Some(created) = card.created {
+        entries.push((created, "created".to_string()));
+    }
+    for transition in &card.history {
+        entries.push((transition.at, format!("moved to {}", transition.column)));
+    }
+
+    let ends = entries
+        .iter()
+        .skip(1)
+        .map(|(at, _)| *at)
+        .chain([now])
+        .collect::<Vec<_>>();
+    entries
+        .into_iter()
+        .zip(ends)
+        .map(|((at, event), end)| (at, event, end.0 - at.0))
+        .collect()
+}
+
+/// The detail pane of the selected card: when it was created and changed, which columns it
+/// went through and how long it has been in its current one.
+pub fn render_details(f: &mut Frame, app: &App) {
+    let theme = &app.config.theme;
+    let (Some(column), Some(card)) = (
+        app.kanban.get_column(app.selected_column),
+        app.kanban.get_card(app.selected_column, app.selected_card),
+    ) else {
+        return;
+    };
+    let now = Timestamp::now();
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            card.title.as_str(),
+            theme.dialog.add_modifier(Modifier::BOLD),
+        )),
+        Line::from(Span::styled(format!("#{}", card.id), theme.hint)),
+        Line::from(""),
+    ];
+    let in_column = match card.entered_column() {
+        Some(entered) => format!(
+            "{}, for {}",
+            column.title,
+            describe_duration(now.0 - entered.0)
+        ),
+        None => column.title.clone(),
+    };
+    for (name, value) in [
+        ("Column ", Some(in_column)),
+        ("Created", card.created.map(local)),
+        ("Updated", card.updated.map(local)),
+    ] {
+        lines.push(Line::from(vec![
+            Span::styled(format!("{}  ", name), theme.hint),
+            Span::raw(value.unwrap_or("unknown".to_string())),
+        ]));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled("History", theme.prompt)));
+    let timeline = timeline(card, now);
+    if timeline.is_empty() {
+        lines.push(Line::from(Span::styled("no moves recorded", theme.hint)));
+    }
+    for (at, event, stayed) in timeline {
+        lines.push(Line::from(vec![
+            Span::styled(format!("{}  ", local(at)), theme.hint),
+            Span::raw(event),
+            Span::styled(format!(" ({})", describe_duration(stayed)), theme.hint),
+        ]));
+    }
+
+    let block = Block::default()
+        .title("Card details")
+        .borders(Borders::ALL)
+        .border_style(theme.dialog);
+    let area = centered_rect(50, 50, f.size());
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        area,
+    );
+}
+
+pub fn events(key: KeyEvent, app: &mut App) {
+    let keymap = &app.config.keymap;
+    let close = matches!(keymap.action(Context::Dialog, key), Some(Action::Deny))
+        || matches!(keymap.action(Context::Main, key), Some(Action::ShowDetails));
+
+    if close {
+        app.current_screen = CurrentScreen::Main;
+    }
+}
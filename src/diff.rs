@@ -0,0 +1,557 @@
+//! What changed between two versions of a board, card by card rather than line by line.
+//!
+//! Cards and columns are matched by ID, so a card that moved and was edited shows up as exactly
+//! that instead of as lines removed in one place and added in another. The newer version of a
+//! board saved without IDs needs loading with [`Kanban::from_lines_like`] against the older one.
+
+use std::{collections::HashMap, fmt::Display};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    check::Check,
+    kanban::{Card, Column, Kanban},
+};
+
+/// One difference between two versions of a board. Cards are named by ID and their title in the
+/// newer version, or the older one when they are gone; columns by title.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(
+        tag = "change",
+        rename_all = "kebab-case",
+        rename_all_fields = "kebab-case"
+    )
+)]
+pub enum Change {
+    BoardRenamed {
+        old: String,
+        new: String,
+    },
+    ColumnAdded {
+        id: String,
+        title: String,
+    },
+    ColumnRemoved {
+        id: String,
+        title: String,
+    },
+    ColumnArchived {
+        id: String,
+        title: String,
+    },
+    ColumnRestored {
+        id: String,
+        title: String,
+    },
+    ColumnRenamed {
+        id: String,
+        old: String,
+        new: String,
+    },
+    /// Moved relative to the other columns.
+    ColumnReordered {
+        id: String,
+        title: String,
+    },
+    CardAdded {
+        id: String,
+        title: String,
+        column: String,
+    },
+    CardRemoved {
+        id: String,
+        title: String,
+        column: String,
+    },
+    CardArchived {
+        id: String,
+        title: String,
+        column: String,
+    },
+    CardRestored {
+        id: String,
+        title: String,
+        column: String,
+    },
+    CardRenamed {
+        id: String,
+        old: String,
+        new: String,
+    },
+    CardMoved {
+        id: String,
+        title: String,
+        from: String,
+        to: String,
+    },
+    /// Moved relative to the other cards of its column.
+    CardReordered {
+        id: String,
+        title: String,
+        column: String,
+    },
+    /// A change to the description, checklist, due date, priority or labels, each written out
+    /// in full.
+    CardChanged {
+        id: String,
+        title: String,
+        field: String,
+        old: String,
+        new: String,
+    },
+}
+
+/// Where a card is, by column ID, or `None` in the archive.
+type Place = Option<String>;
+
+/// Every card of a board by ID, with where it is and the title of that place.
+fn index(kanban: &Kanban) -> HashMap<&str, (&Card, Place, String)> {
+    let mut cards = HashMap::new();
+    for column in kanban.columns() {
+        for card in &column.cards {
+            let place = (card, Some(column.id.clone()), column.title.clone());
+            cards.insert(card.id.as_str(), place);
+        }
+    }
+    for archived in kanban.archive() {
+        let place = (&archived.card, None, archived.column.clone());
+        cards.insert(archived.card.id.as_str(), place);
+    }
+
+    cards
+}
+
+fn is_archived(kanban: &Kanban, column_id: &str) -> bool {
+    kanban
+        .archived_columns()
+        .iter()
+        .any(|archived| archived.column.id == column_id)
+}
+
+fn column_ids(kanban: &Kanban) -> Vec<&str> {
+    kanban
+        .columns()
+        .iter()
+        .map(|column| column.id.as_str())
+        .collect()
+}
+
+fn card_ids(column: &Column) -> Vec<&str> {
+    column.cards.iter().map(|card| card.id.as_str()).collect()
+}
+
+/// The items of `old` that are out of order in `new`: those outside a longest common subsequence
+/// of the items the two have in common.
+fn reordered<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<&'a str> {
+    let old = old
+        .iter()
+        .copied()
+        .filter(|id| new.contains(id))
+        .collect::<Vec<_>>();
+    let new = new
+        .iter()
+        .copied()
+        .filter(|id| old.contains(id))
+        .collect::<Vec<_>>();
+
+    // lengths[i][j]: the longest common subsequence of old[i..] and new[j..].
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = match old[i] == new[j] {
+                true => lengths[i + 1][j + 1] + 1,
+                false => lengths[i + 1][j].max(lengths[i][j + 1]),
+            };
+        }
+    }
+
+    let mut in_order = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            in_order.push(old[i]);
+            (i, j) = (i + 1, j + 1);
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+
+    new.into_iter()
+        .filter(|id| !in_order.contains(id))
+        .collect()
+}
+
+fn describe_checklist(checklist: &[Check]) -> String {
+    checklist
+        .iter()
+        .map(|check| format!("[{}] {}", if check.done { "x" } else { " " }, check.title))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The differences between the fields of one card, other than where it is.
+fn card_changes(old: &Card, new: &Card, changes: &mut Vec<Change>) {
+    if old.title != new.title {
+        changes.push(Change::CardRenamed {
+            id: new.id.clone(),
+            old: old.title.clone(),
+            new: new.title.clone(),
+        });
+    }
+
+    let or_empty = |value: Option<String>| value.unwrap_or_default();
+    let fields = [
+        (
+            "description",
+            old.description.clone(),
+            new.description.clone(),
+        ),
+        (
+            "checklist",
+            describe_checklist(&old.checklist),
+            describe_checklist(&new.checklist),
+        ),
+        (
+            "due",
+            or_empty(old.due.map(|due| due.to_string())),
+            or_empty(new.due.map(|due| due.to_string())),
+        ),
+        (
+            "priority",
+            or_empty(old.priority.map(|priority| priority.to_string())),
+            or_empty(new.priority.map(|priority| priority.to_string())),
+        ),
+        ("labels", old.labels.join(", "), new.labels.join(", ")),
+    ];
+    for (field, old, new_value) in fields {
+        if old != new_value {
+            changes.push(Change::CardChanged {
+                id: new.id.clone(),
+                title: new.title.clone(),
+                field: field.to_string(),
+                old,
+                new: new_value,
+            });
+        }
+    }
+}
+
+/// Lists what changed from `old` to `new`: the board, then columns and then cards in the order
+/// of the newer board, with what was removed last.
+pub fn diff(old: &Kanban, new: &Kanban) -> Vec<Change> {
+    let mut changes = Vec::new();
+    if old.title() != new.title() {
+        changes.push(Change::BoardRenamed {
+            old: old.title().to_string(),
+            new: new.title().to_string(),
+        });
+    }
+
+    let moved_columns = reordered(&column_ids(old), &column_ids(new));
+    for column in new.columns() {
+        let Some(old_column) = old.find_column_by_id(&column.id).map(|i| &old.columns()[i]) else {
+            let (id, title) = (column.id.clone(), column.title.clone());
+            changes.push(match is_archived(old, &column.id) {
+                true => Change::ColumnRestored { id, title },
+                false => Change::ColumnAdded { id, title },
+            });
+            continue;
+        };
+        if old_column.title != column.title {
+            changes.push(Change::ColumnRenamed {
+                id: column.id.clone(),
+                old: old_column.title.clone(),
+                new: column.title.clone(),
+            });
+        }
+        if moved_columns.contains(&column.id.as_str()) {
+            changes.push(Change::ColumnReordered {
+                id: column.id.clone(),
+                title: column.title.clone(),
+            });
+        }
+    }
+
+    let (old_cards, new_cards) = (index(old), index(new));
+    let new_order = new
+        .columns()
+        .iter()
+        .flat_map(|column| &column.cards)
+        .chain(new.archive().iter().map(|archived| &archived.card));
+    for card in new_order {
+        let (_, new_place, new_column) = &new_cards[card.id.as_str()];
+        let Some((old_card, old_place, old_column)) = old_cards.get(card.id.as_str()) else {
+            changes.push(match new_place {
+                Some(_) => Change::CardAdded {
+                    id: card.id.clone(),
+                    title: card.title.clone(),
+                    column: new_column.clone(),
+                },
+                None => Change::CardArchived {
+                    id: card.id.clone(),
+                    title: card.title.clone(),
+                    column: new_column.clone(),
+                },
+            });
+            continue;
+        };
+
+        let (id, title) = (card.id.clone(), card.title.clone());
+        match (old_place, new_place) {
+            (Some(_), None) => changes.push(Change::CardArchived {
+                id,
+                title,
+                column: old_column.clone(),
+            }),
+            (None, Some(_)) => changes.push(Change::CardRestored {
+                id,
+                title,
+                column: new_column.clone(),
+            }),
+            (Some(old_place), Some(new_place)) if old_place != new_place => {
+                changes.push(Change::CardMoved {
+                    id,
+                    title,
+                    from: old_column.clone(),
+                    to: new_column.clone(),
+                });
+            }
+            _ => {}
+        }
+        card_changes(old_card, card, &mut changes);
+    }
+
+    // Reordering within a column, once the moves in and out are left aside.
+    for column in new.columns() {
+        let Some(old_column) = old.find_column_by_id(&column.id).map(|i| &old.columns()[i]) else {
+            continue;
+        };
+        for id in reordered(&card_ids(old_column), &card_ids(column)) {
+            changes.push(Change::CardReordered {
+                id: id.to_string(),
+                title: new_cards[id].0.title.clone(),
+                column: column.title.clone(),
+            });
+        }
+    }
+
+    for column in old.columns() {
+        if new.find_column_by_id(&column.id).is_none() {
+            let (id, title) = (column.id.clone(), column.title.clone());
+            changes.push(match is_archived(new, &column.id) {
+                true => Change::ColumnArchived { id, title },
+                false => Change::ColumnRemoved { id, title },
+            });
+        }
+    }
+    let old_order = old
+        .columns()
+        .iter()
+        .flat_map(|column| &column.cards)
+        .chain(old.archive().iter().map(|archived| &archived.card));
+    for card in old_order {
+        if !new_cards.contains_key(card.id.as_str()) {
+            let (_, place, column) = &old_cards[card.id.as_str()];
+            changes.push(Change::CardRemoved {
+                id: card.id.clone(),
+                title: card.title.clone(),
+                column: match place {
+                    Some(_) => column.clone(),
+                    None => "archive".to_string(),
+                },
+            });
+        }
+    }
+
+    changes
+}
+
+impl Display for Change {
+    /// One line per change, starting with `+` for what is new, `-` for what is gone and `~` for
+    /// the rest. Changed fields follow on indented `-` and `+` lines.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            // Against an empty file, for a board that was created or deleted.
+            Self::BoardRenamed { old, new } if old.is_empty() => write!(f, "+ board {}", new),
+            Self::BoardRenamed { old, new } if new.is_empty() => write!(f, "- board {}", old),
+            Self::BoardRenamed { old, new } => write!(f, "~ board renamed: {} -> {}", old, new),
+            Self::ColumnAdded { title, .. } => write!(f, "+ column {}", title),
+            Self::ColumnRemoved { title, .. } => write!(f, "- column {}", title),
+            Self::ColumnArchived { title, .. } => write!(f, "- column {} archived", title),
+            Self::ColumnRestored { title, .. } => write!(f, "+ column {} restored", title),
+            Self::ColumnRenamed { old, new, .. } => {
+                write!(f, "~ column renamed: {} -> {}", old, new)
+            }
+            Self::ColumnReordered { title, .. } => write!(f, "~ column {} reordered", title),
+            Self::CardAdded { id, title, column } => {
+                write!(f, "+ #{} {} in {}", id, title, column)
+            }
+            Self::CardRemoved { id, title, column } => {
+                write!(f, "- #{} {} from {}", id, title, column)
+            }
+            Self::CardArchived { id, title, column } => {
+                write!(f, "- #{} {} archived from {}", id, title, column)
+            }
+            Self::CardRestored { id, title, column } => {
+                write!(f, "+ #{} {} restored to {}", id, title, column)
+            }
+            Self::CardRenamed { id, old, new } => {
+                write!(f, "~ #{} renamed: {} -> {}", id, old, new)
+            }
+            Self::CardMoved {
+                id,
+                title,
+                from,
+                to,
+            } => write!(f, "~ #{} {} moved: {} -> {}", id, title, from, to),
+            Self::CardReordered { id, title, column } => {
+                write!(f, "~ #{} {} reordered in {}", id, title, column)
+            }
+            Self::CardChanged {
+                id,
+                title,
+                field,
+                old,
+                new,
+            } => {
+                write!(f, "~ #{} {} {}", id, title, field)?;
+                let old = old.lines().collect::<Vec<_>>();
+                let new = new.lines().collect::<Vec<_>>();
+                for line in old.iter().filter(|line| !new.contains(line)) {
+                    write!(f, "\n    - {}", line)?;
+                }
+                for line in new.iter().filter(|line| !old.contains(line)) {
+                    write!(f, "\n    + {}", line)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ParseMode;
+
+    fn board() -> Kanban {
+        let cards = vec![Card::new("A", ""), Card::new("B", ""), Card::new("C", "")];
+        let columns = vec![Column::new("Todo", cards), Column::new("Done", Vec::new())];
+        Kanban::with_columns("Board", columns)
+    }
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn finds_nothing_between_copies_of_a_board() {
+        let kanban = board();
+        assert_eq!(diff(&kanban, &kanban.clone()), []);
+
+        for text in [
+            "Board\n\tTodo\n\t\tA\n\t\tB\n\tDone",
+            "#!crabmat 3\nBoard\n\tTodo\n\t\t@id abcd\n\t\tA\n\t\t\t@id bcde\n\t\tB",
+        ] {
+            let (old, _) = Kanban::from_lines(&lines(text), ParseMode::Strict).unwrap();
+            let (new, _) = Kanban::from_lines_like(&lines(text), ParseMode::Strict, &old).unwrap();
+            assert_eq!(diff(&old, &new), [], "{}", text);
+        }
+    }
+
+    #[test]
+    fn reports_a_card_moved_and_edited_as_such() {
+        let old = board();
+        let mut new = old.clone();
+        new.move_card(0, 1, 0).unwrap();
+        new.set_card_description(1, 0, "Edited").unwrap();
+
+        let id = old.columns()[0].cards[0].id.clone();
+        assert_eq!(
+            diff(&old, &new),
+            [
+                Change::CardMoved {
+                    id: id.clone(),
+                    title: "A".to_string(),
+                    from: "Todo".to_string(),
+                    to: "Done".to_string(),
+                },
+                Change::CardChanged {
+                    id,
+                    title: "A".to_string(),
+                    field: "description".to_string(),
+                    old: String::new(),
+                    new: "Edited".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_only_the_cards_out_of_order() {
+        let old = board();
+        let mut new = old.clone();
+        new.swap_card(0, 0, 1).unwrap();
+        new.swap_card(0, 1, 2).unwrap();
+        new.add_card(0, Card::new("D", "")).unwrap();
+
+        let changes = diff(&old, &new);
+        assert_eq!(changes.len(), 2, "{:?}", changes);
+        assert_eq!(
+            changes[1],
+            Change::CardReordered {
+                id: old.columns()[0].cards[0].id.clone(),
+                title: "A".to_string(),
+                column: "Todo".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn reports_archiving_and_restoring() {
+        let old = board();
+        let mut archived = old.clone();
+        archived.archive_card(0, 1).unwrap();
+        let id = old.columns()[0].cards[1].id.clone();
+        let change = |restored| {
+            let (id, title, column) = (id.clone(), "B".to_string(), "Todo".to_string());
+            match restored {
+                false => Change::CardArchived { id, title, column },
+                true => Change::CardRestored { id, title, column },
+            }
+        };
+
+        assert_eq!(diff(&old, &archived), [change(false)]);
+        let mut restored = archived.clone();
+        restored.restore_card(0, None).unwrap();
+        let changes = diff(&archived, &restored);
+        assert_eq!(changes[0], change(true));
+        assert_eq!(changes.len(), 1, "{:?}", changes);
+    }
+
+    #[test]
+    fn reports_columns_archived_and_restored() {
+        let old = board();
+        let mut archived = old.clone();
+        archived.archive_column(1).unwrap();
+        let (id, title) = (old.columns()[1].id.clone(), "Done".to_string());
+
+        let archived_column = Change::ColumnArchived {
+            id: id.clone(),
+            title: title.clone(),
+        };
+        assert_eq!(diff(&old, &archived), [archived_column]);
+        let mut restored = archived.clone();
+        restored.restore_column(0).unwrap();
+        assert_eq!(
+            diff(&archived, &restored),
+            [Change::ColumnRestored { id, title }]
+        );
+    }
+}
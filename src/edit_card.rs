@@ -1,26 +1,31 @@
+use crabmat::{Card, Due};
 use crossterm::event::KeyEvent;
 use ratatui::{
     layout::{Constraint, Direction, Layout},
-    style::{Color, Style},
-    text::Text,
-    widgets::{Block, Borders, Clear},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem},
     Frame,
 };
 use tui_textarea::{Input, Key};
 
 use crate::{
-    app::{App, CurrentScreen, CurrentlyEditing},
-    kanban::Card,
+    app::{App, CurrentlyEditing},
+    keymap::{Action, Context},
+    labels,
     ui::centered_rect,
 };
 
 pub fn render_card_screen(f: &mut Frame, app: &mut App, is_new: bool) {
+    let title = match app.kanban.get_card(app.selected_column, app.selected_card) {
+        Some(card) if !is_new => format!("Editing card #{}", card.id),
+        _ => "Editing new card".to_string(),
+    };
     let popup_block = Block::default()
-        .title(format!("Editing {}card", if is_new { "new " } else { "" }))
+        .title(title)
         .borders(Borders::ALL)
-        .style(Style::default());
+        .border_style(app.config.theme.dialog);
 
-    let area = centered_rect(60, 25, f.size());
+    let area = centered_rect(60, 40, f.size());
     f.render_widget(Clear, area);
     f.render_widget(popup_block, area);
 
@@ -30,99 +35,285 @@ pub fn render_card_screen(f: &mut Frame, app: &mut App, is_new: bool) {
         .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
         .split(area);
 
-    let style = Style::default().fg(Color::White);
+    let style = app.config.theme.dialog;
 
     let left_block = Block::default().borders(Borders::RIGHT).style(style);
     let left_layout = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Max(3), Constraint::Min(1)])
+        .constraints([
+            Constraint::Max(3),
+            Constraint::Min(1),
+            Constraint::Length(2),
+        ])
         .split(card_editor[0]);
 
+    app.refresh_labels();
+    let label_rows = app.labels.len().clamp(1, 5) as u16 + 1;
     let right_layout = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Percentage(100)])
+        .constraints([
+            Constraint::Min(1),
+            Constraint::Length(1),
+            Constraint::Length(label_rows),
+            Constraint::Length(1),
+        ])
         .split(card_editor[1]);
 
     let title_block = Block::default().borders(Borders::BOTTOM).style(style);
     let description_block = Block::default().borders(Borders::NONE).style(style);
+    let due_block = Block::default()
+        .title("Due (YYYY-MM-DD [HH:MM])")
+        .borders(Borders::TOP)
+        .style(style);
 
     app.set_title_block(title_block);
     app.set_description_block(description_block);
+    app.set_due_block(due_block);
 
     f.render_widget(app.title_input.widget(), left_layout[0]);
     f.render_widget(app.description_input.widget(), left_layout[1]);
-    f.render_widget(Text::from("Checklist TBD"), right_layout[0]);
+    f.render_widget(app.due_input.widget(), left_layout[2]);
+    render_checklist(f, app, right_layout[0]);
+    if matches!(app.currently_editing, Some(CurrentlyEditing::Check(_))) {
+        f.render_widget(app.check_input.widget(), right_layout[1]);
+    }
+    render_labels(f, app, right_layout[2]);
+    if matches!(app.currently_editing, Some(CurrentlyEditing::NewLabel)) {
+        f.render_widget(app.label_input.widget(), right_layout[3]);
+    }
 
     f.render_widget(left_block, card_editor[0]);
 }
 
+fn render_labels(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let focused = matches!(
+        app.currently_editing,
+        Some(CurrentlyEditing::Labels | CurrentlyEditing::NewLabel)
+    );
+    let block = Block::default()
+        .title(format!(
+            " Labels <{}>",
+            app.config.keymap.key(Action::EditLabels)
+        ))
+        .borders(Borders::NONE)
+        .style(if focused {
+            app.config.theme.dialog
+        } else {
+            app.config.theme.hint
+        });
+
+    labels::render_list(f, app, area, block, &app.card_labels, focused);
+}
+
+fn render_checklist(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let focused = matches!(
+        app.currently_editing,
+        Some(CurrentlyEditing::Checklist | CurrentlyEditing::Check(_))
+    );
+    let done = app.checklist.iter().filter(|check| check.done).count();
+    let block = Block::default()
+        .title(format!(" Checklist {}/{}", done, app.checklist.len()))
+        .borders(Borders::NONE)
+        .style(if focused {
+            app.config.theme.dialog
+        } else {
+            app.config.theme.hint
+        });
+
+    let mut items = Vec::<ListItem>::new();
+    if app.checklist.is_empty() {
+        let keymap = &app.config.keymap;
+        items.push(ListItem::new(Line::from(format!(
+            " <{}> then {} to add",
+            keymap.key(Action::EditChecklist),
+            keymap.key(Action::AddCheck)
+        ))));
+    }
+
+    for (i, check) in app.checklist.iter().enumerate() {
+        let style = if focused && app.selected_check == i {
+            app.config.theme.selected_card
+        } else {
+            app.config.theme.card
+        };
+
+        items.push(ListItem::new(Line::from(Span::styled(
+            format!(" {}", check),
+            style,
+        ))));
+    }
+
+    f.render_widget(List::new(items).block(block), area);
+}
+
 pub fn events(key: KeyEvent, is_new: bool, app: &mut App) {
-    if let Some(editing) = &app.currently_editing {
-        match Input::from(key) {
-            Input {
-                key: Key::Char('o') | Key::Char('O'),
-                ..
-            } if app.vim.is_normal()
-                && !matches!(app.currently_editing, Some(CurrentlyEditing::Description)) => {}
-            Input {
-                key: Key::Enter, ..
-            } if app.vim.is_insert()
-                && !matches!(app.currently_editing, Some(CurrentlyEditing::Description)) =>
-            {
-                if !app.title_value().is_empty() {
-                    app.update_vim(Input::from(key));
-                    app.edit_description();
-                }
-            }
-            Input {
-                key: Key::Char('s') | Key::Enter,
-                ..
-            } if app.vim.is_normal() => {
-                if is_new {
-                    app.kanban.add_card(
-                        app.selected_column,
-                        Card::new(
-                            &app.title_input.lines().concat(),
-                            &app.description_input.lines().join("\t\t\t\n"),
-                        ),
-                    );
-                } else {
-                    app.kanban.set_card_title(
-                        app.selected_column,
-                        app.selected_card,
-                        &app.title_input.lines().join("\n"),
-                    );
-                    app.kanban.set_card_description(
-                        app.selected_column,
-                        app.selected_card,
-                        &app.description_input.lines().join("\n"),
-                    );
-                }
-                app.save_kanban();
-                app.current_screen = CurrentScreen::Main;
-            }
-            Input {
-                key: Key::Char('q') | Key::Esc,
-                ..
-            } if app.vim.is_normal() => app.stop_edit(),
-            Input {
-                key: Key::Char('j'),
-                ctrl: true,
-                ..
-            } if app.vim.is_normal() && matches!(editing, CurrentlyEditing::Title) => {
+    match app.currently_editing {
+        Some(CurrentlyEditing::Checklist) => return checklist_events(key, is_new, app),
+        Some(CurrentlyEditing::Check(is_new_check)) => return check_events(key, is_new_check, app),
+        Some(CurrentlyEditing::Labels) => return labels_events(key, is_new, app),
+        Some(CurrentlyEditing::NewLabel) => return new_label_events(key, app),
+        _ => {}
+    }
+
+    let Some(editing) = &app.currently_editing else {
+        return;
+    };
+    let is_description = matches!(editing, CurrentlyEditing::Description);
+    let is_due = matches!(editing, CurrentlyEditing::Due);
+
+    match Input::from(key) {
+        Input {
+            key: Key::Char('o') | Key::Char('O'),
+            ..
+        } if app.vim.is_normal() && !is_description => return,
+        Input {
+            key: Key::Enter, ..
+        } if app.vim.is_insert() && is_due => {
+            app.update_vim(Input {
+                key: Key::Esc,
+                ..Input::default()
+            });
+            return;
+        }
+        Input {
+            key: Key::Enter, ..
+        } if app.vim.is_insert() && !is_description => {
+            if !app.title_value().is_empty() {
                 app.update_vim(Input::from(key));
                 app.edit_description();
             }
-            Input {
-                key: Key::Char('k'),
-                ctrl: true,
-                ..
-            } if app.vim.is_normal() && matches!(editing, CurrentlyEditing::Description) => {
-                app.edit_title();
-            }
-            _ => {
-                app.update_vim(Input::from(key));
-            }
+            return;
+        }
+        _ => {}
+    }
+
+    let action = match app.vim.is_normal() {
+        true => app.config.keymap.action(Context::Editor, key),
+        false => None,
+    };
+    match action {
+        Some(Action::Save) => save(is_new, app),
+        Some(Action::Cancel) => app.stop_edit(),
+        Some(Action::EditDescription) if !is_description => {
+            app.update_vim(Input::from(key));
+            app.edit_description();
+        }
+        Some(Action::EditTitle) if is_description => app.edit_title(),
+        Some(Action::EditChecklist) => app.edit_checklist(),
+        Some(Action::EditDue) if !is_due => app.edit_due(),
+        Some(Action::EditLabels) => app.edit_labels(),
+        _ => {
+            app.update_vim(Input::from(key));
+        }
+    }
+}
+
+fn checklist_events(key: KeyEvent, is_new: bool, app: &mut App) {
+    match app.config.keymap.action(Context::Checklist, key) {
+        Some(Action::LeaveChecklist) => app.edit_description(),
+        Some(Action::CheckDown) => app.increment_selected_check(),
+        Some(Action::CheckUp) => app.decrement_selected_check(),
+        Some(Action::MoveCheckDown) => app.move_check_down(),
+        Some(Action::MoveCheckUp) => app.move_check_up(),
+        Some(Action::ToggleCheck) => app.toggle_check(),
+        Some(Action::DeleteCheck) => app.delete_check(),
+        Some(Action::AddCheck) => app.edit_check(true),
+        Some(Action::EditCheck) => app.edit_check(false),
+        Some(Action::Save) => save(is_new, app),
+        Some(Action::Cancel) => app.stop_edit(),
+        _ => {}
+    }
+}
+
+fn labels_events(key: KeyEvent, is_new: bool, app: &mut App) {
+    match app.config.keymap.action(Context::Labels, key) {
+        Some(Action::LeaveLabels) => app.edit_description(),
+        Some(Action::LabelDown) => app.increment_selected_label(),
+        Some(Action::LabelUp) => app.decrement_selected_label(),
+        Some(Action::ToggleLabel) => app.toggle_card_label(),
+        Some(Action::NewLabel) => app.new_label(),
+        Some(Action::EditChecklist) => app.edit_checklist(),
+        Some(Action::Save) => save(is_new, app),
+        Some(Action::Cancel) => app.stop_edit(),
+        _ => {}
+    }
+}
+
+/// What a key does in a single line input of the card editor: the editor's save and cancel keys
+/// in normal mode, and the prompt's accept key in any mode.
+fn line_action(key: KeyEvent, app: &App) -> Option<Action> {
+    let keymap = &app.config.keymap;
+    match keymap.action(Context::Prompt, key) {
+        Some(Action::Accept) => Some(Action::Save),
+        _ if app.vim.is_normal() => keymap.action(Context::Editor, key),
+        _ => None,
+    }
+}
+
+fn new_label_events(key: KeyEvent, app: &mut App) {
+    match line_action(key, app) {
+        Some(Action::Save) => app.save_label(),
+        Some(Action::Cancel) => app.edit_labels(),
+        _ => {
+            app.update_vim(Input::from(key));
+        }
+    }
+}
+
+fn check_events(key: KeyEvent, is_new: bool, app: &mut App) {
+    match line_action(key, app) {
+        Some(Action::Save) => app.save_check(is_new),
+        Some(Action::Cancel) => app.edit_checklist(),
+        _ => {
+            app.update_vim(Input::from(key));
         }
     }
 }
+
+fn save(is_new: bool, app: &mut App) {
+    let (column, card) = (app.selected_column, app.selected_card);
+    let description = app.description_input.lines().join("\n");
+    let checklist = app.checklist.clone();
+    let labels = app.card_labels.clone();
+    let due = app.due_input.lines().concat();
+    let due = match due.trim() {
+        "" => None,
+        due => match due.parse::<Due>() {
+            Ok(due) => Some(due),
+            Err(_) => {
+                app.message = Some(format!(
+                    "invalid due date `{}`, expected YYYY-MM-DD [HH:MM]",
+                    due
+                ));
+                return;
+            }
+        },
+    };
+
+    let _ = if is_new {
+        let mut card = Card::from(&app.title_input.lines().concat(), &description, &checklist);
+        card.due = due;
+        let added = app.update_kanban(|kanban| {
+            kanban.add_card(column, card)?;
+            let card = kanban.columns()[column].cards.len() - 1;
+            kanban.set_card_labels(column, card, &labels)
+        });
+        if let Err(e) = added {
+            // Keep the editor open so the card is not lost.
+            app.message = Some(app.describe_error(e));
+            return;
+        }
+        app.warn_over_limit(column);
+        added
+    } else {
+        let title = app.title_input.lines().join("\n");
+        app.update_kanban(|kanban| {
+            kanban.set_card_title(column, card, &title)?;
+            kanban.set_card_description(column, card, &description)?;
+            kanban.set_card_checklist(column, card, &checklist)?;
+            kanban.set_card_due(column, card, due)?;
+            kanban.set_card_labels(column, card, &labels)
+        })
+    };
+    app.stop_edit();
+}
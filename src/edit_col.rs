@@ -1,15 +1,15 @@
+use crabmat::Column;
 use crossterm::event::KeyEvent;
 use ratatui::{
     layout::{Constraint, Direction, Layout},
-    style::{Color, Style},
     widgets::{Block, Borders, Clear},
     Frame,
 };
 use tui_textarea::{Input, Key};
 
 use crate::{
-    app::{App, CurrentScreen},
-    kanban::Column,
+    app::App,
+    keymap::{Action, Context},
     ui::centered_rect,
 };
 
@@ -21,7 +21,7 @@ pub fn render_edit_col(f: &mut Frame, app: &mut App, is_new: bool) {
             "Editing column"
         })
         .borders(Borders::ALL)
-        .style(Style::default());
+        .border_style(app.config.theme.dialog);
 
     let area = centered_rect(60, 25, f.size());
     f.render_widget(Clear, area);
@@ -33,57 +33,86 @@ pub fn render_edit_col(f: &mut Frame, app: &mut App, is_new: bool) {
         .constraints([Constraint::Percentage(100)])
         .split(area);
 
-    let style = Style::default().fg(Color::White);
+    let style = app.config.theme.dialog;
 
     let left_block = Block::default().borders(Borders::NONE).style(style);
     let left_layout = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Percentage(100)])
+        .constraints([Constraint::Min(1), Constraint::Length(2)])
         .split(col_editor[0]);
 
     let title_block = Block::default().borders(Borders::NONE).style(style);
+    let limit_block = Block::default()
+        .title(format!(
+            "WIP limit <{}>",
+            app.config.keymap.key(Action::EditLimit)
+        ))
+        .borders(Borders::TOP)
+        .style(style);
 
     app.set_title_block(title_block);
+    app.set_limit_block(limit_block);
 
     f.render_widget(app.title_input.widget(), left_layout[0]);
+    f.render_widget(app.limit_input.widget(), left_layout[1]);
 
     f.render_widget(left_block, col_editor[0]);
 }
 
 // TODO: remove repeated code for editors
 pub fn events(key: KeyEvent, is_new: bool, app: &mut App) {
-    if let Some(_editing) = &app.currently_editing {
-        match Input::from(key) {
-            Input {
-                key: Key::Char('o') | Key::Char('O'),
-                ..
-            } if app.vim.is_normal() => {}
-            Input {
-                key: Key::Enter, ..
-            } if app.vim.is_insert() => save(is_new, app),
-            Input {
-                key: Key::Char('s') | Key::Enter,
-                ..
-            } if app.vim.is_normal() => save(is_new, app),
-            Input {
-                key: Key::Char('q') | Key::Esc,
-                ..
-            } if app.vim.is_normal() => app.stop_edit(),
-            _ => {
-                app.update_vim(Input::from(key));
-            }
-        }
+    if app.currently_editing.is_none() {
+        return;
+    }
+
+    match Input::from(key) {
+        Input {
+            key: Key::Char('o') | Key::Char('O'),
+            ..
+        } if app.vim.is_normal() => {}
+        Input {
+            key: Key::Enter, ..
+        } if app.vim.is_insert() => save(is_new, app),
+        input if app.vim.is_normal() => match app.config.keymap.action(Context::Editor, key) {
+            Some(Action::Save) => save(is_new, app),
+            Some(Action::Cancel) => app.stop_edit(),
+            Some(Action::EditTitle) => app.edit_title(),
+            Some(Action::EditLimit) => app.edit_limit(),
+            _ => app.update_vim(input),
+        },
+        input => app.update_vim(input),
     }
 }
 
 fn save(is_new: bool, app: &mut App) {
-    if is_new {
-        app.kanban
-            .add_column(Column::new(&app.title_input.lines().concat(), Vec::new()));
+    let column = app.selected_column;
+    let limit = match app.limit_input.lines().concat().trim() {
+        "" => None,
+        limit => match limit.parse::<usize>() {
+            Ok(limit) if limit > 0 => Some(limit),
+            _ => {
+                app.message = Some(format!(
+                    "invalid WIP limit `{}`, expected a positive number",
+                    limit
+                ));
+                return;
+            }
+        },
+    };
+
+    let _ = if is_new {
+        let mut new_column = Column::new(&app.title_input.lines().concat(), Vec::new());
+        new_column.limit = limit;
+        app.update_kanban(|kanban| {
+            kanban.add_column(new_column);
+            Ok(())
+        })
     } else {
-        app.kanban
-            .set_col_title(app.selected_column, &app.title_input.lines().join("\n"));
-    }
-    app.save_kanban();
-    app.current_screen = CurrentScreen::Main;
+        let title = app.title_input.lines().join("\n");
+        app.update_kanban(|kanban| {
+            kanban.set_col_title(column, &title)?;
+            kanban.set_col_limit(column, limit)
+        })
+    };
+    app.stop_edit();
 }
@@ -0,0 +1,159 @@
+use std::{error::Error, fmt::Display};
+
+/// Why a single line of a board file could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorReason {
+    /// The line is indented deeper (or shallower) than any known item.
+    UnexpectedIndent(usize),
+    /// A column line with no title.
+    EmptyColumnTitle,
+    /// A card line with no title.
+    EmptyCardTitle,
+    /// A card that appears before any column.
+    CardWithoutColumn,
+    /// A description line that appears before any card.
+    DescriptionWithoutCard,
+    /// A checklist line that appears before any card.
+    CheckWithoutCard,
+    /// A checklist line that does not start with `[x] ` or `[ ] `.
+    InvalidCheck,
+    /// A backslash followed by anything other than `\\`, `t`, `n`, `r` or `@`.
+    InvalidEscape,
+    /// A property line with a name that does not belong on the item above it.
+    UnknownProperty(String),
+    /// A property line whose value cannot be read, such as a malformed date.
+    InvalidProperty(String),
+    /// A property line that appears before the item it belongs to.
+    PropertyWithoutItem,
+    /// A format header naming a version this build cannot read.
+    UnsupportedVersion,
+    /// A line that is not part of the board's shape, such as a Markdown paragraph.
+    UnexpectedLine,
+    /// A Markdown board that does not start with a `# ` title.
+    MissingTitle,
+}
+
+impl Display for ParseErrorReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnexpectedIndent(tabs) => write!(f, "unexpected indentation of {} tabs", tabs),
+            Self::EmptyColumnTitle => write!(f, "column has no title"),
+            Self::EmptyCardTitle => write!(f, "card has no title"),
+            Self::CardWithoutColumn => write!(f, "card is not inside a column"),
+            Self::DescriptionWithoutCard => write!(f, "description is not inside a card"),
+            Self::CheckWithoutCard => write!(f, "checklist item is not inside a card"),
+            Self::InvalidCheck => write!(f, "checklist item must start with [x] or [ ]"),
+            Self::InvalidEscape => write!(f, "invalid escape sequence"),
+            Self::UnknownProperty(name) => write!(f, "unknown property @{}", name),
+            Self::InvalidProperty(name) => write!(f, "invalid value for @{}", name),
+            Self::PropertyWithoutItem => write!(f, "property is not inside a column or card"),
+            Self::UnsupportedVersion => write!(f, "unsupported format version"),
+            Self::UnexpectedLine => {
+                write!(f, "line is not a title, column, card or checklist item")
+            }
+            Self::MissingTitle => write!(f, "board must start with a `# ` title"),
+        }
+    }
+}
+
+/// A line of a board file that could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line number in the file, counting the `#!crabmat` header line when there is one.
+    pub line: usize,
+    pub text: String,
+    pub reason: ParseErrorReason,
+}
+
+impl ParseError {
+    pub fn new(line: usize, text: &str, reason: ParseErrorReason) -> Self {
+        Self {
+            line,
+            text: text.to_string(),
+            reason,
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}: {:?}", self.line, self.reason, self.text)
+    }
+}
+
+impl Error for ParseError {}
+
+/// Everything that can go wrong while loading a board file.
+#[derive(Debug)]
+pub enum LoadError {
+    Io(std::io::Error),
+    /// The file exists but has no title line.
+    Empty,
+    Parse(ParseError),
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{}", e),
+            Self::Empty => write!(f, "empty file given"),
+            Self::Parse(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Empty => None,
+            Self::Parse(e) => Some(e),
+        }
+    }
+}
+
+impl From<std::io::Error> for LoadError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<ParseError> for LoadError {
+    fn from(value: ParseError) -> Self {
+        Self::Parse(value)
+    }
+}
+
+/// A `Kanban` mutation that refers to something that is not on the board, or that the board's
+/// rules do not allow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KanbanError {
+    /// No column at this index.
+    ColumnNotFound(usize),
+    /// No card at this `(column, card)` index.
+    CardNotFound(usize, usize),
+    /// The column at this index is at its WIP limit and the board refuses more cards.
+    ColumnFull(usize),
+    /// No card at this index in the archive.
+    ArchivedCardNotFound(usize),
+    /// No column at this index in the archive.
+    ArchivedColumnNotFound(usize),
+}
+
+impl Display for KanbanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ColumnNotFound(column) => write!(f, "no column at index {}", column),
+            Self::CardNotFound(column, card) => {
+                write!(f, "no card at index {} in column {}", card, column)
+            }
+            Self::ColumnFull(column) => write!(f, "column {} is at its WIP limit", column),
+            Self::ArchivedCardNotFound(idx) => write!(f, "no archived card at index {}", idx),
+            Self::ArchivedColumnNotFound(idx) => {
+                write!(f, "no archived column at index {}", idx)
+            }
+        }
+    }
+}
+
+impl Error for KanbanError {}
@@ -1,11 +1,14 @@
 use std::io;
 
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crabmat::Priority;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
 use tui_textarea::{CursorMove, Input, TextArea};
 
 use crate::{
     app::{App, CurrentScreen},
-    delete_card, delete_col, edit_card, edit_col,
+    archive, command, conflict, delete_card, delete_col, details, edit_card, edit_col,
+    keymap::{Action, Context},
+    labels, search,
 };
 
 pub fn handle_events(app: &mut App) -> io::Result<bool> {
@@ -13,120 +16,100 @@ pub fn handle_events(app: &mut App) -> io::Result<bool> {
         if key.kind == event::KeyEventKind::Release {
             return Ok(true);
         }
+        let (column, card) = (app.selected_column, app.selected_card);
+        if matches!(app.current_screen, CurrentScreen::Main) {
+            app.message = None;
+        }
         match app.current_screen {
-            CurrentScreen::Main => match key.code {
-                KeyCode::Char('w') => {
-                    println!("kanban: {}", app.kanban);
-                }
-                KeyCode::Char('q') => {
+            CurrentScreen::Main => match app.config.keymap.action(Context::Main, key) {
+                Some(action) if action.edits_board() && app.refuse_read_only() => {}
+                // A held back save is answered in the conflict prompt before leaving.
+                Some(Action::Quit) if app.conflict => app.current_screen = CurrentScreen::Conflict,
+                Some(Action::Quit) => {
                     return Ok(true);
                 }
 
-                KeyCode::Char('h') | KeyCode::Right => {
-                    if key.modifiers == KeyModifiers::CONTROL {
-                        if app.selected_column > 0 {
-                            app.kanban
-                                .swap_column(app.selected_column - 1, app.selected_column);
-                            app.save_kanban();
-                            app.decrement_selected_column();
-                        }
-                    } else {
-                        app.decrement_selected_column();
-                    }
+                // History
+                Some(Action::Undo) => app.undo(),
+                Some(Action::Redo) => app.redo(),
+
+                Some(Action::SwapColumnLeft)
+                    if column > 0
+                        && app
+                            .update_kanban(|kanban| kanban.swap_column(column - 1, column))
+                            .is_ok() =>
+                {
+                    app.decrement_selected_column();
                 }
-                KeyCode::Char('l') | KeyCode::Left => {
-                    if key.modifiers == KeyModifiers::CONTROL {
-                        if app.kanban.columns().len() > 0
-                            && app.selected_column < app.kanban.columns().len() - 1
-                        {
-                            app.kanban
-                                .swap_column(app.selected_column, app.selected_column + 1);
-                            app.save_kanban();
-                            app.increment_selected_column();
-                        }
-                    } else {
-                        app.increment_selected_column();
+                Some(Action::SwapColumnRight)
+                    if app
+                        .update_kanban(|kanban| kanban.swap_column(column, column + 1))
+                        .is_ok() =>
+                {
+                    app.increment_selected_column();
+                }
+                Some(Action::FocusLeft) => app.decrement_selected_column(),
+                Some(Action::FocusRight) => app.increment_selected_column(),
+                Some(Action::FocusDown) => app.step_visible_card(true),
+                Some(Action::FocusUp) => app.step_visible_card(false),
+
+                // Command line
+                Some(Action::CommandLine) => command::start(app),
+
+                // Search
+                Some(Action::Search) => app.start_search(),
+                Some(Action::NextMatch) => app.next_hit(),
+                Some(Action::PreviousMatch) => app.previous_hit(),
+                Some(Action::ClearSearch) => {
+                    app.clear_search();
+                    app.label_filter.clear();
+                }
+                Some(Action::FilterLabels) => app.start_label_filter(),
+                Some(Action::ShowArchive) => archive::start(app),
+                Some(Action::ShowDetails) if app.kanban.get_card(column, card).is_some() => {
+                    app.current_screen = CurrentScreen::Details;
+                }
+
+                // Priority
+                Some(action @ (Action::RaisePriority | Action::LowerPriority)) => {
+                    if let Some(current) = app.kanban.get_card(column, card).map(|c| c.priority) {
+                        let priority = match action {
+                            Action::RaisePriority => Priority::raise(current),
+                            _ => Priority::lower(current),
+                        };
+                        let _ = app.update_kanban(|kanban| {
+                            kanban.set_card_priority(column, card, priority)
+                        });
                     }
                 }
-                KeyCode::Char('j') | KeyCode::Up => {
-                    app.decrement_selected_card();
-                }
-                KeyCode::Char('k') | KeyCode::Down => {
-                    app.increment_selected_card();
-                }
 
                 // Move card
-                KeyCode::Char('H') => {
-                    if app
-                        .kanban
-                        .get_card(app.selected_column, app.selected_card)
-                        .is_some()
-                    {
-                        app.kanban.move_card(
-                            app.selected_column,
-                            app.selected_column - 1,
-                            app.selected_card,
-                        );
-                        app.save_kanban();
-                        app.decrement_selected_card();
-                    }
+                Some(Action::MoveCardLeft)
+                    if column > 0 && app.move_card(column, column - 1, card) =>
+                {
+                    app.decrement_selected_card();
                 }
-                KeyCode::Char('L') => {
-                    if app
-                        .kanban
-                        .get_card(app.selected_column, app.selected_card)
-                        .is_some()
-                    {
-                        app.kanban.move_card(
-                            app.selected_column,
-                            app.selected_column + 1,
-                            app.selected_card,
-                        );
-                        app.save_kanban();
-                        app.decrement_selected_card();
-                    }
+                Some(Action::MoveCardRight) if app.move_card(column, column + 1, card) => {
+                    app.decrement_selected_card();
                 }
-                KeyCode::Char('J') => {
-                    if let Some(column) = app.kanban.get_column(app.selected_column) {
-                        if app.selected_card < column.cards.len() - 1
-                            && app
-                                .kanban
-                                .get_card(app.selected_column, app.selected_card + 1)
-                                .is_some()
-                        {
-                            app.kanban.swap_card(
-                                app.selected_column,
-                                app.selected_card,
-                                app.selected_card + 1,
-                            );
-                            app.decrement_selected_card();
-                            app.save_kanban();
-                        }
-                    }
+                Some(Action::MoveCardDown)
+                    if app
+                        .update_kanban(|kanban| kanban.swap_card(column, card, card + 1))
+                        .is_ok() =>
+                {
+                    app.decrement_selected_card();
                 }
-                KeyCode::Char('K') => {
-                    if app.selected_card > 0
-                        && app
-                            .kanban
-                            .get_card(app.selected_column, app.selected_card)
-                            .is_some()
+                Some(Action::MoveCardUp)
+                    if card > 0
                         && app
-                            .kanban
-                            .get_card(app.selected_column, app.selected_card - 1)
-                            .is_some()
-                    {
-                        app.kanban.swap_card(
-                            app.selected_column,
-                            app.selected_card,
-                            app.selected_card - 1,
-                        );
-                        app.increment_selected_card();
-                        app.save_kanban();
-                    }
+                            .update_kanban(|kanban| kanban.swap_card(column, card, card - 1))
+                            .is_ok() =>
+                {
+                    app.increment_selected_card();
                 }
 
                 // Update
-                KeyCode::Char('e') | KeyCode::Enter => {
+                Some(Action::EditCard) => {
                     if let Some(card) = app.kanban.get_card(app.selected_column, app.selected_card)
                     {
                         app.title_input = TextArea::new(vec![card.title.to_string()]);
@@ -138,6 +121,14 @@ pub fn handle_events(app: &mut App) -> io::Result<bool> {
                                 .collect(),
                         );
                         app.description_input.move_cursor(CursorMove::End);
+                        app.due_input = TextArea::new(vec![card
+                            .due
+                            .map(|due| due.to_string())
+                            .unwrap_or_default()]);
+                        app.due_input.move_cursor(CursorMove::End);
+                        app.checklist = card.checklist.clone();
+                        app.card_labels = card.labels.clone();
+                        app.selected_check = 0;
 
                         app.current_screen = CurrentScreen::Card(false);
                         app.edit_title();
@@ -145,10 +136,15 @@ pub fn handle_events(app: &mut App) -> io::Result<bool> {
                     }
                 }
 
-                KeyCode::Char('E') => {
+                Some(Action::EditColumn) => {
                     if let Some(col) = app.kanban.get_column(app.selected_column) {
                         app.title_input = TextArea::new(vec![col.title.to_string()]);
                         app.title_input.move_cursor(CursorMove::End);
+                        app.limit_input = TextArea::new(vec![col
+                            .limit
+                            .map(|limit| limit.to_string())
+                            .unwrap_or_default()]);
+                        app.limit_input.move_cursor(CursorMove::End);
 
                         app.current_screen = CurrentScreen::Col(false);
                         app.edit_title();
@@ -156,7 +152,7 @@ pub fn handle_events(app: &mut App) -> io::Result<bool> {
                 }
 
                 // Create
-                KeyCode::Char('C') => {
+                Some(Action::NewColumn) => {
                     app.title_input = TextArea::new(vec![]);
                     app.title_input.move_cursor(CursorMove::End);
                     app.description_input = TextArea::new(vec![]);
@@ -167,28 +163,26 @@ pub fn handle_events(app: &mut App) -> io::Result<bool> {
                     app.update_vim(Input::from(KeyEvent::from(KeyCode::Char('A'))));
                 }
 
-                KeyCode::Char('c') => {
-                    if app.kanban.get_column(app.selected_column).is_some() {
-                        app.title_input = TextArea::new(vec![]);
-                        app.title_input.move_cursor(CursorMove::End);
-                        app.description_input = TextArea::new(vec![]);
-                        app.description_input.move_cursor(CursorMove::End);
+                Some(Action::NewCard) if app.kanban.get_column(app.selected_column).is_some() => {
+                    app.title_input = TextArea::new(vec![]);
+                    app.title_input.move_cursor(CursorMove::End);
+                    app.description_input = TextArea::new(vec![]);
+                    app.description_input.move_cursor(CursorMove::End);
 
-                        app.current_screen = CurrentScreen::Card(true);
-                        app.edit_title();
-                        app.update_vim(Input::from(KeyEvent::from(KeyCode::Char('A'))));
-                    }
+                    app.current_screen = CurrentScreen::Card(true);
+                    app.edit_title();
+                    app.update_vim(Input::from(KeyEvent::from(KeyCode::Char('A'))));
                 }
 
                 // Delete
-                KeyCode::Char('d') => {
+                Some(Action::DeleteCard) => {
                     if let Some(card) = app.kanban.get_card(app.selected_column, app.selected_card)
                     {
                         app.current_screen = CurrentScreen::DeleteCard(card.clone());
                     }
                 }
 
-                KeyCode::Char('D') => {
+                Some(Action::DeleteColumn) => {
                     if let Some(col) = app.kanban.get_column(app.selected_column) {
                         app.current_screen = CurrentScreen::DeleteCol(col.clone());
                     }
@@ -202,6 +196,16 @@ pub fn handle_events(app: &mut App) -> io::Result<bool> {
             CurrentScreen::Col(is_new) if key.kind == KeyEventKind::Press => {
                 edit_col::events(key, is_new, app)
             }
+            CurrentScreen::Search if key.kind == KeyEventKind::Press => search::events(key, app),
+            CurrentScreen::LabelFilter if key.kind == KeyEventKind::Press => {
+                labels::filter_events(key, app)
+            }
+            CurrentScreen::Archive if key.kind == KeyEventKind::Press => archive::events(key, app),
+            CurrentScreen::Details if key.kind == KeyEventKind::Press => details::events(key, app),
+            CurrentScreen::Command if key.kind == KeyEventKind::Press => command::events(key, app),
+            CurrentScreen::Conflict if key.kind == KeyEventKind::Press => {
+                conflict::events(key, app)
+            }
             CurrentScreen::DeleteCard(_) if key.kind == KeyEventKind::Press => {
                 delete_card::events(key, app)
             }
@@ -209,11 +213,9 @@ pub fn handle_events(app: &mut App) -> io::Result<bool> {
                 delete_col::events(key, app)
             }
 
-            _ => match key.code {
-                _ => {}
-            },
+            _ => {}
         }
     }
 
-    Ok(false)
+    Ok(app.quit)
 }
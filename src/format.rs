@@ -0,0 +1,152 @@
+/// First line of every board written by this version of crabmat. Files without it are read with
+/// the original (version 1) rules, where every tab in a line counts towards its nesting.
+pub const FORMAT_HEADER: &str = "#!crabmat ";
+pub const FORMAT_VERSION: u32 = 3;
+
+/// Starts a property line such as `@due 2024-05-01`, which belongs to the item one level up.
+/// Since version 3, text starting with it is escaped as `\@`.
+pub const PROPERTY_PREFIX: char = '@';
+
+/// Escapes text so that it contains no tabs or line breaks and can be stored after the nesting
+/// tabs of a single line without being mistaken for a property.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    if text.starts_with(PROPERTY_PREFIX) {
+        escaped.push('\\');
+    }
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Reverses `escape`, returning `None` on an unknown or unterminated escape sequence.
+pub fn unescape(text: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next()? {
+            '\\' => unescaped.push('\\'),
+            't' => unescaped.push('\t'),
+            'n' => unescaped.push('\n'),
+            'r' => unescaped.push('\r'),
+            PROPERTY_PREFIX => unescaped.push(PROPERTY_PREFIX),
+            _ => return None,
+        }
+    }
+
+    Some(unescaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kanban::{Card, Column, Kanban, ParseMode};
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    fn round_trip(kanban: &Kanban) -> Kanban {
+        let (parsed, errors) =
+            Kanban::from_lines(&lines(&kanban.to_string()), ParseMode::Strict).unwrap();
+        assert!(errors.is_empty());
+        parsed
+    }
+
+    #[test]
+    fn escapes_round_trip() {
+        for text in [
+            "back\\slash",
+            "\\",
+            "\\t is not a tab",
+            "a\ttab",
+            "two\nlines",
+            "carriage\r\nreturn",
+            "@due tomorrow",
+            "\\@",
+            "  leading spaces",
+            "",
+            "\n\n",
+        ] {
+            let escaped = escape(text);
+            assert!(!escaped.contains(['\t', '\n', '\r']), "{:?}", escaped);
+            assert!(!escaped.starts_with(PROPERTY_PREFIX), "{:?}", escaped);
+            assert_eq!(unescape(&escaped).as_deref(), Some(text));
+        }
+    }
+
+    #[test]
+    fn rejects_bad_escapes() {
+        assert_eq!(unescape("trailing\\"), None);
+        assert_eq!(unescape("\\x"), None);
+    }
+
+    #[test]
+    fn board_text_round_trips() {
+        let card = Card::new(
+            "@not a property\twith a tab",
+            "\n  indented\n\\ and a backslash\n\n@also not a property\n",
+        );
+        let column = Column::new("  spaced out  ", vec![card]);
+        let kanban = Kanban::with_columns("line\nbreak", vec![column]);
+
+        let parsed = round_trip(&kanban);
+        assert_eq!(parsed.title(), kanban.title());
+        assert_eq!(parsed.columns(), kanban.columns());
+    }
+
+    #[test]
+    fn writes_the_current_version() {
+        let text = Kanban::new("Board").to_string();
+        let header = text.lines().next().unwrap();
+        assert_eq!(header, format!("{}{}", FORMAT_HEADER, FORMAT_VERSION));
+    }
+
+    #[test]
+    fn reads_boards_without_a_header_as_version_1() {
+        // Every tab counts towards nesting and text is not unescaped.
+        let (kanban, _) = Kanban::from_lines(
+            &lines("Board\n\tTodo\n\t\tCard \\n\n\t\t\tFirst\n\t\t\tSecond"),
+            ParseMode::Strict,
+        )
+        .unwrap();
+        assert_eq!(kanban.title(), "Board");
+        let card = &kanban.columns()[0].cards[0];
+        assert_eq!(card.title, "Card \\n");
+        assert_eq!(card.description, "First\nSecond");
+    }
+
+    #[test]
+    fn reads_escapes_and_properties_from_version_3() {
+        let (kanban, _) = Kanban::from_lines(
+            &lines("#!crabmat 3\nBoard\n\tTodo\n\t\tCard \\t\n\t\t\t@id abcd\n\t\t\t\\@due"),
+            ParseMode::Strict,
+        )
+        .unwrap();
+        let card = &kanban.columns()[0].cards[0];
+        assert_eq!(card.title, "Card \t");
+        assert_eq!(card.id, "abcd");
+        assert_eq!(card.description, "@due");
+    }
+
+    #[test]
+    fn rejects_unknown_versions() {
+        for header in ["#!crabmat 0", "#!crabmat 99", "#!crabmat three"] {
+            let text = format!("{}\nBoard", header);
+            assert!(Kanban::from_lines(&lines(&text), ParseMode::Lenient).is_err());
+        }
+    }
+}
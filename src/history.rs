@@ -0,0 +1,143 @@
+use std::{collections::VecDeque, mem};
+
+use crate::kanban::Kanban;
+
+/// How many board states `History::default` keeps.
+pub const DEFAULT_HISTORY_LIMIT: usize = 100;
+
+/// Bounded undo/redo history of whole board states.
+#[derive(Debug, Clone)]
+pub struct History {
+    undo: VecDeque<Kanban>,
+    redo: Vec<Kanban>,
+    limit: usize,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        History::new(DEFAULT_HISTORY_LIMIT)
+    }
+}
+
+impl History {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            limit,
+        }
+    }
+
+    /// Records the state of the board before a mutation, dropping the oldest state when the
+    /// history is full. Any undone states can no longer be redone.
+    pub fn record(&mut self, before: Kanban) {
+        self.redo.clear();
+        self.push_undo(before);
+    }
+
+    /// Replaces `current` with the state before the last mutation. Returns `false` when there is
+    /// nothing to undo.
+    pub fn undo(&mut self, current: &mut Kanban) -> bool {
+        let Some(previous) = self.undo.pop_back() else {
+            return false;
+        };
+
+        self.redo.push(mem::replace(current, previous));
+        true
+    }
+
+    /// Reapplies the last undone mutation. Returns `false` when there is nothing to redo.
+    pub fn redo(&mut self, current: &mut Kanban) -> bool {
+        let Some(next) = self.redo.pop() else {
+            return false;
+        };
+
+        let previous = mem::replace(current, next);
+        self.push_undo(previous);
+        true
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    fn push_undo(&mut self, kanban: Kanban) {
+        if self.limit == 0 {
+            return;
+        }
+
+        if self.undo.len() == self.limit {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(kanban);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(title: &str) -> Kanban {
+        Kanban::new(title)
+    }
+
+    #[test]
+    fn undoes_and_redoes_in_order() {
+        let mut history = History::default();
+        let mut current = board("1");
+        history.record(mem::replace(&mut current, board("2")));
+        history.record(mem::replace(&mut current, board("3")));
+
+        assert!(history.undo(&mut current));
+        assert_eq!(current.title(), "2");
+        assert!(history.undo(&mut current));
+        assert_eq!(current.title(), "1");
+        assert!(!history.undo(&mut current));
+
+        assert!(history.redo(&mut current));
+        assert!(history.redo(&mut current));
+        assert_eq!(current.title(), "3");
+        assert!(!history.redo(&mut current));
+    }
+
+    #[test]
+    fn drops_the_oldest_state_at_the_limit() {
+        let mut history = History::new(2);
+        let mut current = board("1");
+        for title in ["2", "3", "4"] {
+            history.record(mem::replace(&mut current, board(title)));
+        }
+
+        assert!(history.undo(&mut current));
+        assert!(history.undo(&mut current));
+        assert_eq!(current.title(), "2");
+        assert!(!history.undo(&mut current));
+
+        let mut disabled = History::new(0);
+        disabled.record(board("1"));
+        assert!(!disabled.can_undo());
+    }
+
+    #[test]
+    fn a_new_edit_clears_redo() {
+        let mut history = History::default();
+        let mut current = board("1");
+        history.record(mem::replace(&mut current, board("2")));
+        assert!(history.undo(&mut current));
+        assert!(history.can_redo());
+
+        history.record(mem::replace(&mut current, board("3")));
+        assert!(!history.can_redo());
+        assert!(history.undo(&mut current));
+        assert_eq!(current.title(), "1");
+    }
+}
@@ -0,0 +1,81 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+};
+
+/// Length, in hex digits, of newly generated IDs.
+pub const ID_LENGTH: usize = 4;
+
+/// Marks an ID in references such as `#a3f9`.
+pub const ID_PREFIX: char = '#';
+
+/// Generates a random short ID for which `taken` returns false. IDs get longer when the short
+/// ones keep colliding, so this never gives up on a crowded board.
+pub fn generate(taken: impl Fn(&str) -> bool) -> String {
+    let mut length = ID_LENGTH;
+    loop {
+        for _ in 0..32 {
+            let id = random_hex(length);
+            if !taken(&id) {
+                return id;
+            }
+        }
+        length += 2;
+    }
+}
+
+/// Derives a short ID from `seed` for which `taken` returns false, the same one every time for
+/// the same seed and taken IDs. Like [`generate`], it gets longer when the short ones collide.
+pub fn derive(seed: &str, taken: impl Fn(&str) -> bool) -> String {
+    let mut length = ID_LENGTH;
+    let mut attempt = 0u32;
+    loop {
+        for _ in 0..32 {
+            let id = hash_hex(seed, attempt, length);
+            attempt += 1;
+            if !taken(&id) {
+                return id;
+            }
+        }
+        length += 2;
+    }
+}
+
+/// Whether `id` can be stored in a board file: non-empty ASCII letters and digits.
+pub fn is_valid(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+fn random_hex(length: usize) -> String {
+    let mut id = String::with_capacity(length);
+    while id.len() < length {
+        // Every `RandomState` is seeded differently, which is all the randomness needed here.
+        let bits = RandomState::new().build_hasher().finish();
+        id.push_str(&format!("{:016x}", bits));
+    }
+
+    id.truncate(length);
+    id
+}
+
+/// FNV-1a over the seed and attempt, which unlike the standard hashers is fixed for good.
+fn hash_hex(seed: &str, attempt: u32, length: usize) -> String {
+    let mut id = String::with_capacity(length);
+    let mut round = 0u32;
+    while id.len() < length {
+        let mut hash = 0xcbf2_9ce4_8422_2325u64;
+        let bytes = seed
+            .bytes()
+            .chain(attempt.to_le_bytes())
+            .chain(round.to_le_bytes());
+        for byte in bytes {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+        id.push_str(&format!("{:016x}", hash));
+        round += 1;
+    }
+
+    id.truncate(length);
+    id
+}
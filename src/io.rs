@@ -1,38 +1,256 @@
-use core::panic;
 use std::{
-    fs::{self, OpenOptions},
-    io::{BufRead, BufReader, Write},
+    fs::{self, File},
+    io::{BufRead, BufReader, ErrorKind, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
 };
 
-use crate::kanban::Kanban;
+use crate::{
+    error::LoadError,
+    kanban::{Kanban, ParseMode},
+};
+
+/// How many backups are kept next to a board, as `.NAME.bak.1` (the newest) to `.NAME.bak.5`.
+pub const BACKUPS: usize = 5;
 
+/// The least time between two backups. Saving every edit would otherwise push anything useful out
+/// of the rotation within a few keystrokes.
+pub const BACKUP_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Writes `kanban` to `path`, creating or replacing the file. The previous version is kept as a
+/// backup when it is old enough, see [`BACKUP_INTERVAL`].
 pub fn save_kanban(kanban: &Kanban, path: &str) -> std::io::Result<()> {
-    let mut file = fs::OpenOptions::new()
-        .write(true)
-        .read(true)
-        .create(true)
-        .truncate(true)
-        .open(&path)?;
-    write!(file, "{}", kanban.to_string())?;
-    Ok(())
+    let path = resolve(path);
+    if backup_due(&path) {
+        backup(&path)?;
+    }
+
+    write_atomic(&path, &kanban.to_string())
 }
 
+/// Reads the raw lines of a board file.
 pub fn read_kanban(path: &str) -> std::io::Result<Vec<String>> {
-    let file = match OpenOptions::new()
-        .write(true)
-        .read(true)
-        .create(true)
-        .open(&path)
-    {
-        Ok(file) => file,
-        Err(e) => {
-            panic!("hello {:?}", e);
+    let file = File::open(path)?;
+    BufReader::new(file).lines().collect()
+}
+
+/// The file a board at `path` actually lives in, following symlinks so saving does not replace
+/// the link with a regular file.
+pub(crate) fn resolve(path: &str) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path))
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+/// Whether the file at `path` should be backed up before it is replaced: when it was written a
+/// while ago, or a while after the newest backup.
+fn backup_due(path: &Path) -> bool {
+    let Some(saved) = modified(path) else {
+        return false;
+    };
+    let Some(newest) = modified(&backup_path(path, 1)) else {
+        return true;
+    };
+
+    let since = |earlier| saved.duration_since(earlier).unwrap_or_default();
+    since(newest) >= BACKUP_INTERVAL
+        || SystemTime::now()
+            .duration_since(saved)
+            .is_ok_and(|age| age >= BACKUP_INTERVAL)
+}
+
+/// Where the `n`th newest backup of the board at `path` is kept.
+pub fn backup_path(path: impl AsRef<Path>, n: usize) -> PathBuf {
+    let path = path.as_ref();
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{}.bak.{}", name, n))
+}
+
+/// The backups of the board at `path` that exist, newest first, with the time each version was
+/// saved.
+pub fn backups(path: &str) -> Vec<(usize, PathBuf, SystemTime)> {
+    let path = resolve(path);
+    (1..=BACKUPS)
+        .filter_map(|n| {
+            let backup = backup_path(&path, n);
+            let saved = modified(&backup)?;
+            Some((n, backup, saved))
+        })
+        .collect()
+}
+
+/// Keeps the current version of the board at `path` as backup 1, shifting the others along and
+/// dropping the oldest. The copy keeps the time the version was saved.
+pub fn backup(path: impl AsRef<Path>) -> std::io::Result<()> {
+    let path = path.as_ref();
+    if !path.exists() {
+        return Ok(());
+    }
+
+    for n in (1..BACKUPS).rev() {
+        match fs::rename(backup_path(path, n), backup_path(path, n + 1)) {
+            Err(e) if e.kind() != ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+    }
+
+    let newest = backup_path(path, 1);
+    fs::copy(path, &newest)?;
+    if let Some(saved) = modified(path) {
+        File::options()
+            .write(true)
+            .open(&newest)?
+            .set_modified(saved)?;
+    }
+    Ok(())
+}
+
+/// Puts backup `n` of the board at `path` back in place, returning the restored board. The
+/// version it replaces becomes the newest backup, so a restore can itself be undone.
+pub fn restore_backup(path: &str, n: usize) -> Result<Kanban, LoadError> {
+    let path = resolve(path);
+    let text = fs::read_to_string(backup_path(&path, n))?;
+    let lines = text.lines().map(String::from).collect::<Vec<_>>();
+    let (kanban, _) = Kanban::from_lines(&lines, ParseMode::Strict)?;
+
+    backup(&path)?;
+    write_atomic(&path, &text)?;
+    Ok(kanban)
+}
+
+/// Replaces `path` with `contents` without ever leaving a half written file behind: the contents
+/// go to a temporary file in the same directory, which is synced to disk and then renamed over
+/// `path`.
+pub fn write_atomic(path: impl AsRef<Path>, contents: &str) -> std::io::Result<()> {
+    let path = path.as_ref();
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let temp = path.with_file_name(format!(".{}.tmp.{}", name, std::process::id()));
+
+    let result = (|| {
+        let mut file = File::create(&temp)?;
+        if let Ok(meta) = fs::metadata(path) {
+            file.set_permissions(meta.permissions())?;
         }
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&temp, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+        return result;
+    }
+
+    // Make the rename itself durable. Not every platform can open a directory, which is fine.
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
     };
-    let buffer = BufReader::new(&file);
-    let data = buffer
-        .lines()
-        .map(|line| line.unwrap())
-        .collect::<Vec<String>>();
-    Ok(data)
+    if let Ok(dir) = File::open(dir) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory for one test, emptied first in case an earlier run left it behind.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("crabmat-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn board(title: &str) -> String {
+        Kanban::new(title).to_string()
+    }
+
+    fn set_age(path: &Path, age: Duration) {
+        let file = File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::now() - age).unwrap();
+    }
+
+    #[test]
+    fn rotates_backups_keeping_the_newest() {
+        let dir = temp_dir("io-rotate");
+        let path = dir.join("kanban");
+        for n in 0..=BACKUPS + 1 {
+            fs::write(&path, board(&n.to_string())).unwrap();
+            backup(&path).unwrap();
+        }
+
+        // Backup 1 is the newest version, backup 5 the oldest kept.
+        for n in 1..=BACKUPS {
+            let text = fs::read_to_string(backup_path(&path, n)).unwrap();
+            assert_eq!(text, board(&(BACKUPS + 2 - n).to_string()));
+        }
+        assert!(!backup_path(&path, BACKUPS + 1).exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn backs_up_at_most_once_an_interval() {
+        let dir = temp_dir("io-interval");
+        let path = dir.join("kanban");
+        assert!(!backup_due(&path));
+
+        fs::write(&path, board("first")).unwrap();
+        assert!(backup_due(&path));
+        backup(&path).unwrap();
+        fs::write(&path, board("second")).unwrap();
+        assert!(!backup_due(&path));
+
+        // Left alone for a while, the saved version is worth keeping again.
+        set_age(&path, BACKUP_INTERVAL);
+        assert!(backup_due(&path));
+
+        // So is one saved well after the newest backup.
+        set_age(&backup_path(&path, 1), BACKUP_INTERVAL * 2);
+        set_age(&path, BACKUP_INTERVAL / 2);
+        assert!(backup_due(&path));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn keeps_the_restored_over_version_as_a_backup() {
+        let dir = temp_dir("io-restore");
+        let path = dir.join("kanban");
+        fs::write(&path, board("old")).unwrap();
+        backup(&path).unwrap();
+        fs::write(&path, board("new")).unwrap();
+
+        let restored = restore_backup(path.to_str().unwrap(), 1).unwrap();
+        assert_eq!(restored.title(), "old");
+        assert_eq!(fs::read_to_string(&path).unwrap(), board("old"));
+        let newest = fs::read_to_string(backup_path(&path, 1)).unwrap();
+        assert_eq!(newest, board("new"));
+        assert_eq!(
+            fs::read_to_string(backup_path(&path, 2)).unwrap(),
+            board("old")
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn keeps_permissions_when_saving() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = temp_dir("io-permissions");
+        let path = dir.join("kanban");
+        fs::write(&path, board("old")).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+
+        write_atomic(&path, &board("new")).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), board("new"));
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+        let leftovers = fs::read_dir(&dir).unwrap().count();
+        assert_eq!(leftovers, 1);
+        fs::remove_dir_all(dir).unwrap();
+    }
 }
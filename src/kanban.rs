@@ -1,63 +1,263 @@
-use std::fmt::Display;
+use std::{cmp::Ordering, fmt::Display, str::FromStr};
 
-use crate::io::{read_kanban, save_kanban};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
+use crate::{
+    check::Check,
+    date::{Due, Timestamp},
+    error::{KanbanError, LoadError, ParseError, ParseErrorReason},
+    format::{escape, unescape, FORMAT_HEADER, FORMAT_VERSION, PROPERTY_PREFIX},
+    id::{self, ID_PREFIX},
+    io::{read_kanban, save_kanban},
+    label::{Label, LABEL_COLORS},
+    priority::Priority,
+};
+
+/// What adding or moving a card into a column at its WIP limit does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum WipPolicy {
+    /// Allow it, leaving the front end to point out the column is over its limit.
+    #[default]
+    Warn,
+    /// Fail with `KanbanError::ColumnFull`.
+    Refuse,
+}
+
+impl Display for WipPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Warn => write!(f, "warn"),
+            Self::Refuse => write!(f, "refuse"),
+        }
+    }
+}
+
+impl FromStr for WipPolicy {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "warn" => Ok(Self::Warn),
+            "refuse" => Ok(Self::Refuse),
+            _ => Err(()),
+        }
+    }
+}
+
+/// How `Kanban::from_lines` treats lines it cannot make sense of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Stop at the first bad line.
+    Strict,
+    /// Skip bad lines and report them.
+    Lenient,
+}
+
+/// A card entering a column, recorded by `Kanban::move_card`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Transition {
+    /// The title of the column the card moved to, at the time it moved.
+    pub column: String,
+    pub at: Timestamp,
+}
+
+/// A card taken off the board, with where it came from so it can be restored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Archived {
+    pub card: Card,
+    /// The title and ID of the column the card was archived from.
+    pub column: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub column_id: String,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub archived: Option<Timestamp>,
+}
+
+/// A column taken off the board, with its ID and WIP limit so it can be restored. Its cards are
+/// archived on their own, pointing back at it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ArchivedColumn {
+    pub column: Column,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub archived: Option<Timestamp>,
+}
+
+/// Where `Kanban::from_lines` is in the file.
+#[derive(Default)]
+struct ParseState {
+    /// Whether the last card already has a description line.
+    has_description: bool,
+    /// Whether the `@archive` marker has been read, after which cards go to the archive.
+    in_archive: bool,
+    /// Whether the last item in the archive is a column, which column properties then go to.
+    in_archived_column: bool,
+}
+
+/// A card on a column, with a free-form description, an optional checklist, an optional due
+/// date and priority, and labels from the board's palette.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Card {
+    /// Short, stable and unique within the board. Empty until the card is added to a board.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "String::is_empty")
+    )]
+    pub id: String,
     pub title: String,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub description: String,
-    // pub checklist: Vec<Check>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub checklist: Vec<Check>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub due: Option<Due>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub labels: Vec<String>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub priority: Option<Priority>,
+    /// When the card was added to the board, unknown for cards older than this field.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub created: Option<Timestamp>,
+    /// When the card was last changed or moved.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub updated: Option<Timestamp>,
+    /// Every move to another column, oldest first.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub history: Vec<Transition>,
 }
 
 impl Card {
     pub fn empty() -> Self {
         Self {
+            id: String::new(),
             title: String::new(),
             description: String::new(),
-            // checklist: Vec::new(),
+            checklist: Vec::new(),
+            due: None,
+            labels: Vec::new(),
+            priority: None,
+            created: None,
+            updated: None,
+            history: Vec::new(),
         }
     }
 
     pub fn new(title: &str, description: &str) -> Self {
         Self {
+            id: String::new(),
             title: title.to_string(),
             description: description.to_string(),
-            // checklist: Vec::new(),
+            checklist: Vec::new(),
+            due: None,
+            labels: Vec::new(),
+            priority: None,
+            created: None,
+            updated: None,
+            history: Vec::new(),
         }
     }
 
-    pub fn from(title: &str, description: &str) -> Self {
-        let card = Card::new(title, description);
+    pub fn from(title: &str, description: &str, checklist: &[Check]) -> Self {
+        let mut card = Card::new(title, description);
 
-        // for check in checklist {
-        //     card.add_check(&check.title, check.done);
-        // }
+        for check in checklist {
+            card.add_check(&check.title, check.done);
+        }
 
         card
     }
 
-    // pub fn add_check(&mut self, title: &str, checked: bool) {
-    //     self.checklist.push(Check::new(title, checked));
-    // }
+    pub fn add_check(&mut self, title: &str, checked: bool) {
+        self.checklist.push(Check::new(title, checked));
+    }
+
+    pub fn has_label(&self, name: &str) -> bool {
+        self.labels.iter().any(|label| label == name)
+    }
+
+    /// When the card entered its current column: its last move, or its creation when it never
+    /// moved.
+    pub fn entered_column(&self) -> Option<Timestamp> {
+        self.history
+            .last()
+            .map(|transition| transition.at)
+            .or(self.created)
+    }
+
+    /// Returns `(done, total)` for the card's checklist.
+    pub fn progress(&self) -> (usize, usize) {
+        let done = self.checklist.iter().filter(|check| check.done).count();
+        (done, self.checklist.len())
+    }
 }
 
-#[derive(Debug, Clone)]
+/// A titled, ordered list of cards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Column {
+    /// Short, stable and unique within the board, sharing one namespace with card IDs. Empty
+    /// until the column is added to a board.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "String::is_empty")
+    )]
+    pub id: String,
     pub title: String,
+    /// The most cards the column should hold at once, if any.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub limit: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub cards: Vec<Card>,
 }
 
 impl Column {
     pub fn empty() -> Self {
         Self {
+            id: String::new(),
             title: String::new(),
+            limit: None,
             cards: Vec::new(),
         }
     }
 
     pub fn new(title: &str, items: Vec<Card>) -> Self {
         Self {
+            id: String::new(),
             title: title.to_string(),
+            limit: None,
             cards: items,
         }
     }
@@ -65,31 +265,161 @@ impl Column {
     pub fn add_card(&mut self, card: Card) {
         self.cards.push(card);
     }
+
+    /// Whether the column holds as many cards as its WIP limit allows, or more.
+    pub fn is_full(&self) -> bool {
+        self.limit.is_some_and(|limit| self.cards.len() >= limit)
+    }
+
+    pub fn is_over_limit(&self) -> bool {
+        self.limit.is_some_and(|limit| self.cards.len() > limit)
+    }
 }
 
+/// A board: a title, a palette of labels and an ordered list of columns.
+///
+/// Cards and columns are addressed by index, which changes as they move, or looked up by their
+/// stable ID. Mutators return a `KanbanError` when an index does not exist instead of panicking.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Kanban {
     title: String,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    labels: Vec<Label>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    wip_policy: WipPolicy,
+    #[cfg_attr(feature = "serde", serde(default))]
     columns: Vec<Column>,
+    /// Archived cards, oldest first. Hidden from the board but kept in the file.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    archive: Vec<Archived>,
+    /// Archived columns, oldest first, without their cards.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    archived_columns: Vec<ArchivedColumn>,
+}
+
+/// Writes a column at one tab, with its properties below it but not its cards.
+fn write_column(
+    f: &mut std::fmt::Formatter<'_>,
+    column: &Column,
+    archived: Option<&ArchivedColumn>,
+) -> std::fmt::Result {
+    writeln!(f, "\t{}", escape(&column.title))?;
+    if !column.id.is_empty() {
+        writeln!(f, "\t\t@id {}", column.id)?;
+    }
+    if let Some(limit) = column.limit {
+        writeln!(f, "\t\t@limit {}", limit)?;
+    }
+    if let Some(archived) = archived {
+        writeln!(
+            f,
+            "\t\t@archived {}",
+            archived.archived.unwrap_or(Timestamp(0))
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Writes a card at two tabs, with its properties, description and checklist below it.
+fn write_card(
+    f: &mut std::fmt::Formatter<'_>,
+    card: &Card,
+    archived: Option<&Archived>,
+) -> std::fmt::Result {
+    writeln!(f, "\t\t{}", escape(&card.title))?;
+    if !card.id.is_empty() {
+        writeln!(f, "\t\t\t@id {}", card.id)?;
+    }
+    if let Some(archived) = archived {
+        let at = archived.archived.unwrap_or(Timestamp(0));
+        let column_id = match archived.column_id.as_str() {
+            "" => "-",
+            id => id,
+        };
+        writeln!(
+            f,
+            "\t\t\t@archived {} {} {}",
+            at,
+            column_id,
+            escape(&archived.column)
+        )?;
+    }
+    if let Some(created) = &card.created {
+        writeln!(f, "\t\t\t@created {}", created)?;
+    }
+    if let Some(updated) = &card.updated {
+        writeln!(f, "\t\t\t@updated {}", updated)?;
+    }
+    for transition in &card.history {
+        writeln!(
+            f,
+            "\t\t\t@moved {} {}",
+            transition.at,
+            escape(&transition.column)
+        )?;
+    }
+    if let Some(priority) = &card.priority {
+        writeln!(f, "\t\t\t@priority {}", priority)?;
+    }
+    if let Some(due) = &card.due {
+        writeln!(f, "\t\t\t@due {}", due)?;
+    }
+    for label in &card.labels {
+        writeln!(f, "\t\t\t@label {}", escape(label))?;
+    }
+    if !card.description.is_empty() {
+        for line in card.description.split('\n') {
+            writeln!(f, "\t\t\t{}", escape(line))?;
+        }
+    }
+    for check in &card.checklist {
+        let done = if check.done { "x" } else { " " };
+        writeln!(f, "\t\t\t\t[{}] {}", done, escape(&check.title))?;
+    }
+
+    Ok(())
 }
 
 impl Display for Kanban {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut display = String::from(self.title());
+        writeln!(f, "{}{}", FORMAT_HEADER, FORMAT_VERSION)?;
+        writeln!(f, "{}", escape(self.title()))?;
+        if self.wip_policy != WipPolicy::default() {
+            writeln!(f, "\t@wip-policy {}", self.wip_policy)?;
+        }
+        for label in &self.labels {
+            writeln!(f, "\t@label {} {}", label.color, escape(&label.name))?;
+        }
 
         for column in self.columns() {
-            display.push_str(&format!("\n\t{}", column.title));
+            write_column(f, column, None)?;
             for card in column.cards.iter() {
-                display.push_str(&format!("\n\t\t{}", card.title));
-                for line in card.description.lines() {
-                    display.push_str(&format!("\n\t\t\t{}", line));
-                }
-                // for check in &card.checklist {
-                //     display.push_str(&format!("\n\t\t\t\t{}", check));
-                // }
+                write_card(f, card, None)?;
+            }
+        }
+
+        if !self.archive.is_empty() || !self.archived_columns.is_empty() {
+            writeln!(f, "\t@archive")?;
+            for archived in &self.archive {
+                write_card(f, &archived.card, Some(archived))?;
+            }
+            for archived in &self.archived_columns {
+                write_column(f, &archived.column, Some(archived))?;
             }
         }
 
-        write!(f, "{}\n", display)
+        Ok(())
     }
 }
 
@@ -97,86 +427,640 @@ impl Kanban {
     pub fn new(title: &str) -> Self {
         Self {
             title: title.to_string(),
+            labels: Vec::new(),
+            wip_policy: WipPolicy::default(),
             columns: Vec::new(),
+            archive: Vec::new(),
+            archived_columns: Vec::new(),
         }
     }
 
-    pub fn from_file(path: &str) -> std::io::Result<Self> {
+    /// Loads a board, failing on the first line that cannot be parsed.
+    pub fn from_file(path: &str) -> Result<Self, LoadError> {
+        let lines = read_kanban(path)?;
+        let (kanban, _) = Kanban::from_lines(&lines, ParseMode::Strict)?;
+
+        Ok(kanban)
+    }
+
+    /// Loads a board, skipping any line that cannot be parsed and returning it as an error
+    /// alongside the board.
+    pub fn from_file_lenient(path: &str) -> Result<(Self, Vec<ParseError>), LoadError> {
         let lines = read_kanban(path)?;
+        Kanban::from_lines(&lines, ParseMode::Lenient)
+    }
+
+    /// Loads a later version of the board `earlier`, failing on the first line that cannot be
+    /// parsed. Columns and cards without an ID keep the ones they had in `earlier`, see
+    /// [`Kanban::from_lines_like`].
+    pub fn from_file_like(path: &str, earlier: &Kanban) -> Result<Self, LoadError> {
+        let lines = read_kanban(path)?;
+        let (kanban, _) = Kanban::from_lines_like(&lines, ParseMode::Strict, earlier)?;
+
+        Ok(kanban)
+    }
+
+    /// Parses a board from the lines of a file, in either the current or the original format.
+    pub fn from_lines(
+        lines: &[String],
+        mode: ParseMode,
+    ) -> Result<(Self, Vec<ParseError>), LoadError> {
+        let (mut kanban, errors) = Kanban::parse_lines(lines, mode)?;
+        kanban.assign_ids();
+        Ok((kanban, errors))
+    }
+
+    /// Parses a later version of the board `earlier` like [`Kanban::from_lines`], except that
+    /// columns and cards without an ID take the ID of the one with the same title in `earlier`,
+    /// so that versions of a board from a file without IDs can be merged and compared.
+    pub fn from_lines_like(
+        lines: &[String],
+        mode: ParseMode,
+        earlier: &Kanban,
+    ) -> Result<(Self, Vec<ParseError>), LoadError> {
+        let (mut kanban, errors) = Kanban::parse_lines(lines, mode)?;
+        kanban.adopt_ids(earlier);
+        kanban.assign_ids();
+        Ok((kanban, errors))
+    }
+
+    /// Parses the lines of a file, leaving columns and cards without an ID in it without one.
+    fn parse_lines(
+        lines: &[String],
+        mode: ParseMode,
+    ) -> Result<(Self, Vec<ParseError>), LoadError> {
+        let (version, start) = match lines.first() {
+            Some(header) if header.starts_with(FORMAT_HEADER) => {
+                match header[FORMAT_HEADER.len()..].trim().parse::<u32>() {
+                    Ok(version) if (1..=FORMAT_VERSION).contains(&version) => (version, 1),
+                    _ => {
+                        let reason = ParseErrorReason::UnsupportedVersion;
+                        return Err(ParseError::new(1, header, reason).into());
+                    }
+                }
+            }
+            _ => (1, 0),
+        };
 
-        // TODO: do i want this or would it better to then prompt for a title?
-        let title = if let Some(title) = lines.first() {
-            title
+        let title = lines.get(start).ok_or(LoadError::Empty)?;
+        let title = if version == 1 {
+            if title.trim().is_empty() {
+                return Err(LoadError::Empty);
+            }
+            title.to_string()
         } else {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                "Empty file given.",
-            ));
+            unescape(title)
+                .ok_or_else(|| ParseError::new(start + 1, title, ParseErrorReason::InvalidEscape))?
         };
-        let mut kanban = Kanban::new(title);
-        let mut col = Column::empty();
-        let mut card = Card::empty();
-        for line in lines.iter().skip(1) {
-            let tabs = line.chars().filter(|c| c == &'\t').count();
-
-            match tabs {
-                1 => {
-                    if !card.title.is_empty() {
-                        col.add_card(card);
-                        card = Card::empty();
-                    }
 
-                    if !col.title.is_empty() {
-                        kanban.add_column(col);
-                        col = Column::empty();
-                    }
+        let mut kanban = Kanban::new(&title);
+        let mut errors = Vec::new();
+        let mut state = ParseState::default();
+        for (i, line) in lines.iter().enumerate().skip(start + 1) {
+            let reason = if version == 1 {
+                kanban.parse_legacy_line(line, &mut state)
+            } else {
+                kanban.parse_line(line, version, &mut state)
+            };
 
-                    col.title = line.trim_start().to_string();
-                    col.cards = Vec::new();
+            if let Err(reason) = reason {
+                let error = ParseError::new(i + 1, line, reason);
+                match mode {
+                    ParseMode::Strict => return Err(error.into()),
+                    ParseMode::Lenient => errors.push(error),
                 }
-                2 => {
-                    if !card.title.is_empty() {
-                        col.add_card(card);
-                        card = Card::empty();
-                    }
+            }
+        }
+
+        Ok((kanban, errors))
+    }
+
+    /// Parses a line of the original format, where every tab counts towards nesting and leading
+    /// whitespace is not significant.
+    fn parse_legacy_line(
+        &mut self,
+        line: &str,
+        state: &mut ParseState,
+    ) -> Result<(), ParseErrorReason> {
+        let tabs = line.chars().filter(|c| c == &'\t').count();
+        let text = line.trim_start();
+
+        match tabs {
+            1 | 2 if text.is_empty() => Err(if tabs == 1 {
+                ParseErrorReason::EmptyColumnTitle
+            } else {
+                ParseErrorReason::EmptyCardTitle
+            }),
+            4 => {
+                let card = self
+                    .last_card_mut()
+                    .ok_or(ParseErrorReason::CheckWithoutCard)?;
+                card.checklist.push(Check::from(&line.to_string()));
+                Ok(())
+            }
+            tabs => self.push_item(tabs, text.to_string(), state),
+        }
+    }
+
+    /// Parses a line of the escaped format, where only leading tabs count towards nesting.
+    fn parse_line(
+        &mut self,
+        line: &str,
+        version: u32,
+        state: &mut ParseState,
+    ) -> Result<(), ParseErrorReason> {
+        let tabs = line.chars().take_while(|c| c == &'\t').count();
+        let raw = &line[tabs..];
 
-                    card.title = line.trim_start().to_string();
+        if version >= 3 {
+            if let Some(property) = raw.strip_prefix(PROPERTY_PREFIX) {
+                let (name, value) = property.split_once(' ').unwrap_or((property, ""));
+                return self.parse_property(tabs, name, value, state);
+            }
+        }
+
+        if tabs == 4 {
+            let card = self
+                .current_card_mut(state)
+                .ok_or(ParseErrorReason::CheckWithoutCard)?;
+            let (done, title) = if let Some(title) = raw.strip_prefix("[x] ") {
+                (true, title)
+            } else if let Some(title) = raw.strip_prefix("[ ] ") {
+                (false, title)
+            } else {
+                return Err(ParseErrorReason::InvalidCheck);
+            };
+            let title = unescape(title).ok_or(ParseErrorReason::InvalidEscape)?;
+            card.add_check(&title, done);
+            return Ok(());
+        }
+
+        let text = unescape(raw).ok_or(ParseErrorReason::InvalidEscape)?;
+        self.push_item(tabs, text, state)
+    }
+
+    /// Applies a `@name value` line to the item it belongs to: the board (1 tab), the last column
+    /// (2 tabs) or the last card (3 tabs). Duplicate IDs are left for `assign_ids` to fix.
+    ///
+    /// Surrounding whitespace is ignored, except in the escaped titles that end some values, where
+    /// it is part of the title.
+    fn parse_property(
+        &mut self,
+        tabs: usize,
+        name: &str,
+        raw: &str,
+        state: &mut ParseState,
+    ) -> Result<(), ParseErrorReason> {
+        let invalid = || ParseErrorReason::InvalidProperty(name.to_string());
+        let value = raw.trim();
+
+        match (tabs, name) {
+            (1, "label") => {
+                let (color, name) = raw.trim_start().split_once(' ').ok_or_else(invalid)?;
+                let name = unescape(name).ok_or(ParseErrorReason::InvalidEscape)?;
+                self.add_label(&name, Some(color));
+                Ok(())
+            }
+            (3, "label") => {
+                let name = unescape(raw).ok_or(ParseErrorReason::InvalidEscape)?;
+                if name.is_empty() {
+                    return Err(invalid());
                 }
-                3 => {
-                    if card.description.is_empty() {
-                        card.description = format!("{}\n", line.trim_start());
-                    } else {
-                        card.description
-                            .push_str(&format!("{}\n", line.trim_start()));
-                    }
+                self.add_label(&name, None);
+                let card = self
+                    .current_card_mut(state)
+                    .ok_or(ParseErrorReason::PropertyWithoutItem)?;
+                if !card.has_label(&name) {
+                    card.labels.push(name);
+                }
+                Ok(())
+            }
+            (1, "archive") => {
+                state.in_archive = true;
+                Ok(())
+            }
+            (3, "archived") => {
+                let mut parts = raw.trim_start().splitn(3, ' ');
+                let (Some(at), Some(column_id), Some(column)) =
+                    (parts.next(), parts.next(), parts.next())
+                else {
+                    return Err(invalid());
+                };
+                let at = at.parse().map_err(|_| invalid())?;
+                let column = unescape(column).ok_or(ParseErrorReason::InvalidEscape)?;
+                let archived = self
+                    .archive
+                    .last_mut()
+                    .filter(|_| state.in_archive)
+                    .ok_or(ParseErrorReason::PropertyWithoutItem)?;
+                archived.archived = Some(at);
+                archived.column = column;
+                archived.column_id = match column_id {
+                    "-" => String::new(),
+                    id => id.to_string(),
+                };
+                Ok(())
+            }
+            (2, "archived") => {
+                let archived = self
+                    .archived_columns
+                    .last_mut()
+                    .filter(|_| state.in_archived_column)
+                    .ok_or(ParseErrorReason::PropertyWithoutItem)?;
+                archived.archived = Some(value.parse().map_err(|_| invalid())?);
+                Ok(())
+            }
+            (1, "wip-policy") => {
+                self.wip_policy = value.parse().map_err(|_| invalid())?;
+                Ok(())
+            }
+            (2, "limit") => {
+                let column = self
+                    .current_column_mut(state)
+                    .ok_or(ParseErrorReason::PropertyWithoutItem)?;
+                column.limit = Some(value.parse().ok().filter(|n| *n > 0).ok_or_else(invalid)?);
+                Ok(())
+            }
+            (2, "id") => {
+                let column = self
+                    .current_column_mut(state)
+                    .ok_or(ParseErrorReason::PropertyWithoutItem)?;
+                if !id::is_valid(value) {
+                    return Err(invalid());
+                }
+                column.id = value.to_string();
+                Ok(())
+            }
+            (3, "id") => {
+                let card = self
+                    .current_card_mut(state)
+                    .ok_or(ParseErrorReason::PropertyWithoutItem)?;
+                if !id::is_valid(value) {
+                    return Err(invalid());
                 }
-                // 4 => {
-                //     card.checklist.push(Check::from(line));
-                // }
-                _ => {}
+                card.id = value.to_string();
+                Ok(())
             }
+            (3, "due") => {
+                let card = self
+                    .current_card_mut(state)
+                    .ok_or(ParseErrorReason::PropertyWithoutItem)?;
+                card.due = Some(value.parse().map_err(|_| invalid())?);
+                Ok(())
+            }
+            (3, "priority") => {
+                let card = self
+                    .current_card_mut(state)
+                    .ok_or(ParseErrorReason::PropertyWithoutItem)?;
+                card.priority = Some(value.parse().map_err(|_| invalid())?);
+                Ok(())
+            }
+            (3, "created") => {
+                let card = self
+                    .current_card_mut(state)
+                    .ok_or(ParseErrorReason::PropertyWithoutItem)?;
+                card.created = Some(value.parse().map_err(|_| invalid())?);
+                Ok(())
+            }
+            (3, "updated") => {
+                let card = self
+                    .current_card_mut(state)
+                    .ok_or(ParseErrorReason::PropertyWithoutItem)?;
+                card.updated = Some(value.parse().map_err(|_| invalid())?);
+                Ok(())
+            }
+            (3, "moved") => {
+                let (at, column) = raw.trim_start().split_once(' ').ok_or_else(invalid)?;
+                let at = at.parse().map_err(|_| invalid())?;
+                let column = unescape(column).ok_or(ParseErrorReason::InvalidEscape)?;
+                let card = self
+                    .current_card_mut(state)
+                    .ok_or(ParseErrorReason::PropertyWithoutItem)?;
+                card.history.push(Transition { column, at });
+                Ok(())
+            }
+            (1..=3, name) => Err(ParseErrorReason::UnknownProperty(name.to_string())),
+            (tabs, _) => Err(ParseErrorReason::UnexpectedIndent(tabs)),
         }
-        if !card.title.is_empty() {
-            col.add_card(card);
+    }
+
+    /// Adds a column (1 tab), card (2 tabs) or description line (3 tabs) to the end of the board.
+    fn push_item(
+        &mut self,
+        tabs: usize,
+        text: String,
+        state: &mut ParseState,
+    ) -> Result<(), ParseErrorReason> {
+        match tabs {
+            1 if state.in_archive => {
+                self.archived_columns.push(ArchivedColumn {
+                    column: Column::new(&text, Vec::new()),
+                    archived: None,
+                });
+                state.in_archived_column = true;
+                Ok(())
+            }
+            2 if state.in_archive => {
+                self.archive.push(Archived {
+                    card: Card::new(&text, ""),
+                    column: String::new(),
+                    column_id: String::new(),
+                    archived: None,
+                });
+                state.has_description = false;
+                state.in_archived_column = false;
+                Ok(())
+            }
+            1 => {
+                self.columns.push(Column::new(&text, Vec::new()));
+                Ok(())
+            }
+            2 => {
+                let col = self
+                    .columns
+                    .last_mut()
+                    .ok_or(ParseErrorReason::CardWithoutColumn)?;
+                col.add_card(Card::new(&text, ""));
+                state.has_description = false;
+                Ok(())
+            }
+            3 => {
+                let card = self
+                    .current_card_mut(state)
+                    .ok_or(ParseErrorReason::DescriptionWithoutCard)?;
+                if state.has_description {
+                    card.description.push('\n');
+                }
+                card.description.push_str(&text);
+                state.has_description = true;
+                Ok(())
+            }
+            tabs => Err(ParseErrorReason::UnexpectedIndent(tabs)),
         }
-        if !col.title.is_empty() {
-            kanban.add_column(col);
+    }
+
+    pub(crate) fn last_card_mut(&mut self) -> Option<&mut Card> {
+        self.columns.last_mut()?.cards.last_mut()
+    }
+
+    /// The card that lines being parsed belong to, in the archive once it has started.
+    fn current_card_mut(&mut self, state: &ParseState) -> Option<&mut Card> {
+        match (state.in_archive, state.in_archived_column) {
+            (true, true) => None,
+            (true, false) => self.archive.last_mut().map(|archived| &mut archived.card),
+            (false, _) => self.last_card_mut(),
         }
+    }
 
-        Ok(kanban)
+    /// The column that lines being parsed belong to, in the archive once it has started.
+    fn current_column_mut(&mut self, state: &ParseState) -> Option<&mut Column> {
+        match (state.in_archive, state.in_archived_column) {
+            (true, true) => self
+                .archived_columns
+                .last_mut()
+                .map(|archived| &mut archived.column),
+            (true, false) => None,
+            (false, _) => self.columns.last_mut(),
+        }
     }
 
-    pub fn _with_columns(title: &str, columns: Vec<Column>) -> Self {
-        Self {
+    pub fn with_columns(title: &str, columns: Vec<Column>) -> Self {
+        let mut kanban = Self {
             title: title.to_string(),
+            labels: Vec::new(),
+            wip_policy: WipPolicy::default(),
             columns,
+            archive: Vec::new(),
+            archived_columns: Vec::new(),
+        };
+        kanban.assign_ids();
+        kanban
+    }
+
+    /// Gives every column and card without an ID, or with one already used earlier in the board,
+    /// one derived from its title and position. Boards from older files or other formats need
+    /// this after loading, and get the same IDs each time until they are saved with them.
+    pub fn assign_ids(&mut self) {
+        let mut seen = std::collections::HashSet::new();
+        let mut missing = Vec::new();
+        for (i, column) in self.columns.iter().enumerate() {
+            if !seen.insert(column.id.clone()) || column.id.is_empty() {
+                missing.push((i, None));
+            }
+            for (j, card) in column.cards.iter().enumerate() {
+                if !seen.insert(card.id.clone()) || card.id.is_empty() {
+                    missing.push((i, Some(j)));
+                }
+            }
+        }
+
+        let mut missing_archived = Vec::new();
+        for (i, archived) in self.archive.iter().enumerate() {
+            if !seen.insert(archived.card.id.clone()) || archived.card.id.is_empty() {
+                missing_archived.push(i);
+            }
+        }
+        let mut missing_archived_columns = Vec::new();
+        for (i, archived) in self.archived_columns.iter().enumerate() {
+            if !seen.insert(archived.column.id.clone()) || archived.column.id.is_empty() {
+                missing_archived_columns.push(i);
+            }
+        }
+
+        for (i, j) in missing {
+            let seed = match j {
+                Some(j) => format!("{}\t{}\t{}", i, j, self.columns[i].cards[j].title),
+                None => format!("{}\t{}", i, self.columns[i].title),
+            };
+            let new_id = id::derive(&seed, |id| seen.contains(id));
+            seen.insert(new_id.clone());
+            match j {
+                Some(j) => self.columns[i].cards[j].id = new_id,
+                None => self.columns[i].id = new_id,
+            }
+        }
+        for i in missing_archived {
+            let seed = format!("archive\t{}\t{}", i, self.archive[i].card.title);
+            let new_id = id::derive(&seed, |id| seen.contains(id));
+            seen.insert(new_id.clone());
+            self.archive[i].card.id = new_id;
+        }
+        for i in missing_archived_columns {
+            let seed = format!("archive\t{}\t{}", i, self.archived_columns[i].column.title);
+            let new_id = id::derive(&seed, |id| seen.contains(id));
+            seen.insert(new_id.clone());
+            self.archived_columns[i].column.id = new_id;
         }
     }
 
+    /// Gives each column and card without an ID the ID of one with the same title in `earlier`
+    /// that is not used here yet, preferring the nearest in position and, for cards, one in the
+    /// same column or likewise archived.
+    fn adopt_ids(&mut self, earlier: &Kanban) {
+        let mut taken = self
+            .columns
+            .iter()
+            .flat_map(|column| {
+                std::iter::once(&column.id).chain(column.cards.iter().map(|card| &card.id))
+            })
+            .chain(self.archive.iter().map(|archived| &archived.card.id))
+            .chain(
+                self.archived_columns
+                    .iter()
+                    .map(|archived| &archived.column.id),
+            )
+            .filter(|id| !id.is_empty())
+            .cloned()
+            .collect::<std::collections::HashSet<_>>();
+
+        for (i, column) in self.columns.iter_mut().enumerate() {
+            if !column.id.is_empty() {
+                continue;
+            }
+            let found = earlier
+                .columns
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| c.title == column.title && !taken.contains(&c.id))
+                .min_by_key(|(j, _)| i.abs_diff(*j));
+            if let Some((_, found)) = found {
+                column.id = found.id.clone();
+                taken.insert(found.id.clone());
+            }
+        }
+        let earlier_archived = earlier
+            .archived_columns
+            .iter()
+            .map(|archived| &archived.column);
+        let columns = earlier
+            .columns
+            .iter()
+            .chain(earlier_archived)
+            .collect::<Vec<_>>();
+        for archived in &mut self.archived_columns {
+            let column = &mut archived.column;
+            if !column.id.is_empty() {
+                continue;
+            }
+            let found = columns
+                .iter()
+                .find(|c| c.title == column.title && !taken.contains(&c.id));
+            if let Some(found) = found {
+                column.id = found.id.clone();
+                taken.insert(found.id.clone());
+            }
+        }
+
+        // Where each earlier card was: its column's ID, or `None` in the archive.
+        let cards = earlier
+            .columns
+            .iter()
+            .flat_map(|column| {
+                let place = Some(column.id.as_str());
+                column
+                    .cards
+                    .iter()
+                    .enumerate()
+                    .map(move |(j, card)| (place, j, card))
+            })
+            .chain(
+                earlier
+                    .archive
+                    .iter()
+                    .enumerate()
+                    .map(|(j, archived)| (None, j, &archived.card)),
+            )
+            .collect::<Vec<_>>();
+        let mut adopt = |card: &mut Card, place: Option<&str>, i: usize| {
+            if !card.id.is_empty() {
+                return;
+            }
+            let found = cards
+                .iter()
+                .filter(|(_, _, c)| c.title == card.title && !taken.contains(&c.id))
+                .min_by_key(|(p, j, _)| (*p != place, i.abs_diff(*j)));
+            if let Some((_, _, found)) = found {
+                card.id = found.id.clone();
+                taken.insert(found.id.clone());
+            }
+        };
+        for column in &mut self.columns {
+            for (i, card) in column.cards.iter_mut().enumerate() {
+                adopt(card, Some(column.id.as_str()), i);
+            }
+        }
+        for (i, archived) in self.archive.iter_mut().enumerate() {
+            adopt(&mut archived.card, None, i);
+        }
+    }
+
+    /// Generates an ID not yet used by any column or card, archived ones included.
+    fn new_id(&self) -> String {
+        id::generate(|id| {
+            self.find_column_by_id(id).is_some()
+                || self.find_card_by_id(id).is_some()
+                || self.archive.iter().any(|archived| archived.card.id == id)
+                || self
+                    .archived_columns
+                    .iter()
+                    .any(|archived| archived.column.id == id)
+        })
+    }
+
     pub fn title(&self) -> &str {
         &self.title
     }
 
+    pub fn set_title(&mut self, title: &str) {
+        self.title = title.to_string();
+    }
+
+    pub fn wip_policy(&self) -> WipPolicy {
+        self.wip_policy
+    }
+
+    pub fn set_wip_policy(&mut self, policy: WipPolicy) {
+        self.wip_policy = policy;
+    }
+
+    /// The board's label palette, in the order labels were added.
+    pub fn labels(&self) -> &[Label] {
+        &self.labels
+    }
+
+    pub fn label(&self, name: &str) -> Option<&Label> {
+        self.labels.iter().find(|label| label.name == name)
+    }
+
+    /// Adds a label to the palette, or recolors it when it is already there. Labels added without
+    /// a color get the next one from `LABEL_COLORS`.
+    pub fn add_label(&mut self, name: &str, color: Option<&str>) {
+        if let Some(label) = self.labels.iter_mut().find(|label| label.name == name) {
+            if let Some(color) = color {
+                label.color = color.to_string();
+            }
+            return;
+        }
+
+        let color = color.unwrap_or(LABEL_COLORS[self.labels.len() % LABEL_COLORS.len()]);
+        self.labels.push(Label::new(name, color));
+    }
+
+    /// Removes a label from the palette and from every card that carries it, archived ones
+    /// included.
+    pub fn remove_label(&mut self, name: &str) {
+        self.labels.retain(|label| label.name != name);
+        let archived = self.archive.iter_mut().map(|archived| &mut archived.card);
+        for card in self
+            .columns
+            .iter_mut()
+            .flat_map(|column| &mut column.cards)
+            .chain(archived)
+        {
+            card.labels.retain(|label| label != name);
+        }
+    }
+
     pub fn columns(&self) -> &Vec<Column> {
         &self.columns
     }
@@ -185,98 +1069,630 @@ impl Kanban {
         self.columns.get(idx)
     }
 
+    pub fn find_column_by_id(&self, id: &str) -> Option<usize> {
+        self.columns.iter().position(|column| column.id == id)
+    }
+
+    /// Returns the `(column, card)` position of the card with the given ID.
+    pub fn find_card_by_id(&self, id: &str) -> Option<(usize, usize)> {
+        self.columns.iter().enumerate().find_map(|(i, column)| {
+            let j = column.cards.iter().position(|card| card.id == id)?;
+            Some((i, j))
+        })
+    }
+
+    /// Finds a column by `#id`, then by exact title, then by case-insensitive title, then by
+    /// 1-based position and finally by ID without the `#`.
+    pub fn find_column(&self, query: &str) -> Option<usize> {
+        if let Some(idx) = query
+            .strip_prefix(ID_PREFIX)
+            .and_then(|id| self.find_column_by_id(id))
+        {
+            return Some(idx);
+        }
+
+        self.columns
+            .iter()
+            .position(|column| column.title == query)
+            .or_else(|| {
+                self.columns
+                    .iter()
+                    .position(|column| column.title.eq_ignore_ascii_case(query))
+            })
+            .or_else(|| match query.parse::<usize>() {
+                Ok(n) if n >= 1 && n <= self.columns.len() => Some(n - 1),
+                _ => None,
+            })
+            .or_else(|| self.find_column_by_id(query))
+    }
+
+    /// Returns the `(column, card)` positions of every card titled `query`, falling back to a
+    /// case-insensitive match when nothing matches exactly. `#id`, or a bare ID that matches no
+    /// title, finds the card with that ID.
+    pub fn find_cards(&self, query: &str) -> Vec<(usize, usize)> {
+        if let Some(found) = query
+            .strip_prefix(ID_PREFIX)
+            .and_then(|id| self.find_card_by_id(id))
+        {
+            return vec![found];
+        }
+
+        let find = |matches: &dyn Fn(&Card) -> bool| {
+            self.columns
+                .iter()
+                .enumerate()
+                .flat_map(|(i, column)| {
+                    column
+                        .cards
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, card)| matches(card))
+                        .map(move |(j, _)| (i, j))
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let exact = find(&|card| card.title == query);
+        if !exact.is_empty() {
+            return exact;
+        }
+
+        let similar = find(&|card| card.title.eq_ignore_ascii_case(query));
+        if !similar.is_empty() {
+            return similar;
+        }
+
+        self.find_card_by_id(query).into_iter().collect()
+    }
+
+    fn column_mut(&mut self, idx: usize) -> Result<&mut Column, KanbanError> {
+        self.columns
+            .get_mut(idx)
+            .ok_or(KanbanError::ColumnNotFound(idx))
+    }
+
+    /// Appends a column, giving it and its cards IDs where they have none.
     pub fn add_column(&mut self, column: Column) {
         self.columns.push(column);
+        self.assign_ids();
     }
 
-    pub fn swap_column(&mut self, first: usize, second: usize) {
-        if self.get_column(first).is_none() || self.get_column(second).is_none() {
-            return;
-        }
+    pub fn swap_column(&mut self, first: usize, second: usize) -> Result<(), KanbanError> {
+        self.column_mut(first)?;
+        self.column_mut(second)?;
 
         self.columns.swap(first, second);
+        Ok(())
     }
 
-    pub fn delete_column(&mut self, idx: usize) {
-        self.columns.remove(idx);
+    /// Removes a column and all of its cards, returning it.
+    pub fn delete_column(&mut self, idx: usize) -> Result<Column, KanbanError> {
+        self.column_mut(idx)?;
+
+        Ok(self.columns.remove(idx))
     }
 
-    pub fn set_col_title(&mut self, column_idx: usize, title: &str) {
-        if self.get_column(column_idx).is_none() {
-            return;
-        }
+    pub fn set_col_title(&mut self, column_idx: usize, title: &str) -> Result<(), KanbanError> {
+        self.column_mut(column_idx)?.title = title.to_string();
+        Ok(())
+    }
 
-        self.columns[column_idx].title = title.to_string();
+    pub fn set_col_limit(
+        &mut self,
+        column_idx: usize,
+        limit: Option<usize>,
+    ) -> Result<(), KanbanError> {
+        self.column_mut(column_idx)?.limit = limit;
+        Ok(())
+    }
+
+    /// Fails with `KanbanError::ColumnFull` when the column cannot take another card under the
+    /// board's WIP policy.
+    fn check_limit(&self, column_idx: usize) -> Result<(), KanbanError> {
+        match self.columns.get(column_idx) {
+            None => Err(KanbanError::ColumnNotFound(column_idx)),
+            Some(column) if self.wip_policy == WipPolicy::Refuse && column.is_full() => {
+                Err(KanbanError::ColumnFull(column_idx))
+            }
+            Some(_) => Ok(()),
+        }
     }
 
     pub fn get_card(&self, column_idx: usize, item_idx: usize) -> Option<&Card> {
         self.get_column(column_idx)?.cards.get(item_idx)
     }
 
-    pub fn add_card(&mut self, column_idx: usize, card: Card) {
-        if self.get_column(column_idx).is_none() {
-            return;
-        }
+    pub fn get_card_mut(&mut self, column_idx: usize, item_idx: usize) -> Option<&mut Card> {
+        self.columns.get_mut(column_idx)?.cards.get_mut(item_idx)
+    }
 
-        self.columns[column_idx].add_card(card);
+    fn card_mut(&mut self, column_idx: usize, item_idx: usize) -> Result<&mut Card, KanbanError> {
+        self.column_mut(column_idx)?
+            .cards
+            .get_mut(item_idx)
+            .ok_or(KanbanError::CardNotFound(column_idx, item_idx))
     }
 
-    pub fn swap_card(&mut self, column_idx: usize, first: usize, second: usize) {
-        if self.get_card(column_idx, first).is_none() || self.get_card(column_idx, second).is_none()
-        {
-            return;
+    /// Applies `edit` to a card, stamping it as updated when that changed anything.
+    fn edit_card(
+        &mut self,
+        column_idx: usize,
+        item_idx: usize,
+        edit: impl FnOnce(&mut Card),
+    ) -> Result<(), KanbanError> {
+        let card = self.card_mut(column_idx, item_idx)?;
+        let before = card.clone();
+        edit(card);
+        if *card != before {
+            card.updated = Some(Timestamp::now());
         }
+        Ok(())
+    }
+
+    /// Appends a card to the end of a column, stamping it with the current time unless it
+    /// already has a creation time and giving it an ID unless it has one. Fails when the column
+    /// is full and the WIP policy refuses more cards.
+    pub fn add_card(&mut self, column_idx: usize, mut card: Card) -> Result<(), KanbanError> {
+        self.check_limit(column_idx)?;
+        card.created.get_or_insert_with(Timestamp::now);
+        if card.id.is_empty() {
+            card.id = self.new_id();
+        }
+        self.column_mut(column_idx)?.add_card(card);
+        Ok(())
+    }
+
+    pub fn swap_card(
+        &mut self,
+        column_idx: usize,
+        first: usize,
+        second: usize,
+    ) -> Result<(), KanbanError> {
+        self.card_mut(column_idx, first)?;
+        self.card_mut(column_idx, second)?;
 
         self.columns[column_idx].cards.swap(first, second);
+        Ok(())
     }
 
-    pub fn move_card(&mut self, column_idx: usize, new_column_idx: usize, card_idx: usize) {
-        if self.get_column(column_idx).is_none() || self.get_column(new_column_idx).is_none() {
-            return;
+    /// Stably sorts the cards of a column.
+    pub fn sort_cards_by(
+        &mut self,
+        column_idx: usize,
+        compare: impl FnMut(&Card, &Card) -> Ordering,
+    ) -> Result<(), KanbanError> {
+        self.column_mut(column_idx)?.cards.sort_by(compare);
+        Ok(())
+    }
+
+    /// Moves a card to the end of another column, recording the move in its history. Fails when
+    /// the other column is full and the WIP policy refuses more cards. A move within one column
+    /// leaves its count alone, so it is always allowed.
+    pub fn move_card(
+        &mut self,
+        column_idx: usize,
+        new_column_idx: usize,
+        card_idx: usize,
+    ) -> Result<(), KanbanError> {
+        self.card_mut(column_idx, card_idx)?;
+        if new_column_idx != column_idx {
+            self.check_limit(new_column_idx)?;
         }
 
-        let card = self.columns[column_idx].cards.swap_remove(card_idx);
+        let mut card = self.columns[column_idx].cards.remove(card_idx);
+        let now = Timestamp::now();
+        card.history.push(Transition {
+            column: self.columns[new_column_idx].title.clone(),
+            at: now,
+        });
+        card.updated = Some(now);
         self.columns[new_column_idx].cards.push(card);
+        Ok(())
     }
 
-    pub fn delete_card(&mut self, column_idx: usize, card_idx: usize) {
-        if column_idx > self.columns.len() {
-            return;
+    /// Removes a card, returning it.
+    pub fn delete_card(&mut self, column_idx: usize, card_idx: usize) -> Result<Card, KanbanError> {
+        self.card_mut(column_idx, card_idx)?;
+
+        Ok(self.columns[column_idx].cards.remove(card_idx))
+    }
+
+    pub fn set_card_title(
+        &mut self,
+        column_idx: usize,
+        item_idx: usize,
+        title: &str,
+    ) -> Result<(), KanbanError> {
+        self.edit_card(column_idx, item_idx, |card| card.title = title.to_string())
+    }
+
+    pub fn set_card_description(
+        &mut self,
+        column_idx: usize,
+        item_idx: usize,
+        description: &str,
+    ) -> Result<(), KanbanError> {
+        self.edit_card(column_idx, item_idx, |card| {
+            card.description = description.to_string()
+        })
+    }
+
+    pub fn set_card_checklist(
+        &mut self,
+        column_idx: usize,
+        item_idx: usize,
+        checklist: &[Check],
+    ) -> Result<(), KanbanError> {
+        self.edit_card(column_idx, item_idx, |card| {
+            card.checklist = checklist.to_vec()
+        })
+    }
+
+    pub fn set_card_due(
+        &mut self,
+        column_idx: usize,
+        item_idx: usize,
+        due: Option<Due>,
+    ) -> Result<(), KanbanError> {
+        self.edit_card(column_idx, item_idx, |card| card.due = due)
+    }
+
+    pub fn set_card_priority(
+        &mut self,
+        column_idx: usize,
+        item_idx: usize,
+        priority: Option<Priority>,
+    ) -> Result<(), KanbanError> {
+        self.edit_card(column_idx, item_idx, |card| card.priority = priority)
+    }
+
+    /// Replaces a card's labels, adding any that are new to the palette.
+    pub fn set_card_labels(
+        &mut self,
+        column_idx: usize,
+        item_idx: usize,
+        labels: &[String],
+    ) -> Result<(), KanbanError> {
+        self.card_mut(column_idx, item_idx)?;
+        for label in labels {
+            self.add_label(label, None);
         }
+        self.edit_card(column_idx, item_idx, |card| card.labels = labels.to_vec())
+    }
 
-        if card_idx > self.columns[column_idx].cards.len() {
-            return;
+    /// Archived cards, oldest first.
+    pub fn archive(&self) -> &[Archived] {
+        &self.archive
+    }
+
+    pub(crate) fn archive_mut(&mut self) -> &mut Vec<Archived> {
+        &mut self.archive
+    }
+
+    /// Archived columns, oldest first.
+    pub fn archived_columns(&self) -> &[ArchivedColumn] {
+        &self.archived_columns
+    }
+
+    pub(crate) fn archived_columns_mut(&mut self) -> &mut Vec<ArchivedColumn> {
+        &mut self.archived_columns
+    }
+
+    /// Moves a card off the board into the archive, remembering the column it came from.
+    pub fn archive_card(&mut self, column_idx: usize, card_idx: usize) -> Result<(), KanbanError> {
+        self.card_mut(column_idx, card_idx)?;
+
+        let column = &mut self.columns[column_idx];
+        let card = column.cards.remove(card_idx);
+        self.archive.push(Archived {
+            card,
+            column: column.title.clone(),
+            column_id: column.id.clone(),
+            archived: Some(Timestamp::now()),
+        });
+        Ok(())
+    }
+
+    /// Archives every card of a column, then moves the column itself, with its ID and WIP limit,
+    /// into the archive.
+    pub fn archive_column(&mut self, column_idx: usize) -> Result<(), KanbanError> {
+        while !self.column_mut(column_idx)?.cards.is_empty() {
+            self.archive_card(column_idx, 0)?;
         }
 
-        self.columns[column_idx].cards.remove(card_idx);
+        let column = self.delete_column(column_idx)?;
+        self.archived_columns.push(ArchivedColumn {
+            column,
+            archived: Some(Timestamp::now()),
+        });
+        Ok(())
     }
 
-    pub fn set_card_title(&mut self, column_idx: usize, item_idx: usize, title: &str) {
-        if column_idx > self.columns.len() {
-            return;
+    /// Takes a column out of the archive and appends it to the board, together with the cards
+    /// archived from it that are still in the archive. Returns where the column ended up.
+    pub fn restore_column(&mut self, archive_idx: usize) -> Result<usize, KanbanError> {
+        if archive_idx >= self.archived_columns.len() {
+            return Err(KanbanError::ArchivedColumnNotFound(archive_idx));
         }
 
-        if item_idx > self.columns[column_idx].cards.len() {
-            return;
+        let mut column = self.archived_columns.remove(archive_idx).column;
+        let now = Timestamp::now();
+        let (cards, rest) = std::mem::take(&mut self.archive)
+            .into_iter()
+            .partition::<Vec<_>, _>(|archived| archived.column_id == column.id);
+        self.archive = rest;
+        for archived in cards {
+            let mut card = archived.card;
+            card.history.push(Transition {
+                column: column.title.clone(),
+                at: now,
+            });
+            card.updated = Some(now);
+            column.cards.push(card);
+        }
+        self.columns.push(column);
+        Ok(self.columns.len() - 1)
+    }
+
+    /// Removes a column from the archive for good, along with the cards archived from it,
+    /// returning it.
+    pub fn delete_archived_column(
+        &mut self,
+        archive_idx: usize,
+    ) -> Result<ArchivedColumn, KanbanError> {
+        if archive_idx >= self.archived_columns.len() {
+            return Err(KanbanError::ArchivedColumnNotFound(archive_idx));
         }
 
-        self.columns[column_idx].cards[item_idx].title = title.to_string();
+        let archived = self.archived_columns.remove(archive_idx);
+        self.archive
+            .retain(|card| card.column_id != archived.column.id);
+        Ok(archived)
     }
 
-    pub fn set_card_description(&mut self, column_idx: usize, item_idx: usize, description: &str) {
-        if column_idx > self.columns.len() {
-            return;
+    /// Takes a card out of the archive and appends it to `column_idx`, or to the column it came
+    /// from when that is `None`. An archived column is restored, without its other cards, and one
+    /// that no longer exists at all is recreated at the end of the board. Returns where the card
+    /// ended up.
+    pub fn restore_card(
+        &mut self,
+        archive_idx: usize,
+        column_idx: Option<usize>,
+    ) -> Result<(usize, usize), KanbanError> {
+        let archived = self
+            .archive
+            .get(archive_idx)
+            .ok_or(KanbanError::ArchivedCardNotFound(archive_idx))?;
+        let column_idx = match column_idx {
+            Some(idx) => Some(idx),
+            None => self
+                .find_column_by_id(&archived.column_id)
+                .filter(|_| !archived.column_id.is_empty())
+                .or_else(|| {
+                    self.columns
+                        .iter()
+                        .position(|column| column.title == archived.column)
+                }),
+        };
+
+        let column_idx = match column_idx {
+            Some(idx) => {
+                self.check_limit(idx)?;
+                idx
+            }
+            None => {
+                let archived_column = self.archived_columns.iter().position(|column| {
+                    !archived.column_id.is_empty() && column.column.id == archived.column_id
+                });
+                let column = match archived_column {
+                    Some(idx) => self.archived_columns.remove(idx).column,
+                    None => {
+                        let mut column = Column::new(&archived.column, Vec::new());
+                        column.id = archived.column_id.clone();
+                        column
+                    }
+                };
+                self.columns.push(column);
+                self.columns.len() - 1
+            }
+        };
+
+        let mut card = self.archive.remove(archive_idx).card;
+        let now = Timestamp::now();
+        card.history.push(Transition {
+            column: self.columns[column_idx].title.clone(),
+            at: now,
+        });
+        card.updated = Some(now);
+        self.columns[column_idx].cards.push(card);
+        self.assign_ids();
+        Ok((column_idx, self.columns[column_idx].cards.len() - 1))
+    }
+
+    /// Removes a card from the archive for good, returning it.
+    pub fn delete_archived(&mut self, archive_idx: usize) -> Result<Archived, KanbanError> {
+        if archive_idx >= self.archive.len() {
+            return Err(KanbanError::ArchivedCardNotFound(archive_idx));
         }
 
-        if item_idx > self.columns[column_idx].cards.len() {
-            return;
+        Ok(self.archive.remove(archive_idx))
+    }
+
+    /// Writes the board to `path` in the current format. The file is replaced in one step, so a
+    /// crash mid-save leaves the old version intact, and older versions are kept as backups, see
+    /// [`crate::io`].
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        save_kanban(self, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removing_a_label_strips_it_from_archived_cards() {
+        let mut card = Card::new("Card", "");
+        card.labels = vec!["bug".to_string(), "ui".to_string()];
+        let mut kanban = Kanban::with_columns("Board", vec![Column::new("Todo", vec![card])]);
+        kanban.add_label("bug", None);
+        kanban.add_label("ui", None);
+        kanban.archive_card(0, 0).unwrap();
+
+        kanban.remove_label("bug");
+        kanban.restore_card(0, None).unwrap();
+        assert_eq!(kanban.columns()[0].cards[0].labels, ["ui"]);
+    }
+
+    fn limited_board(policy: WipPolicy) -> Kanban {
+        let columns = vec![
+            Column::new("Todo", vec![Card::new("A", ""), Card::new("B", "")]),
+            Column::new("Doing", vec![Card::new("C", "")]),
+        ];
+        let mut kanban = Kanban::with_columns("Board", columns);
+        kanban.set_col_limit(1, Some(1)).unwrap();
+        kanban.set_wip_policy(policy);
+        kanban
+    }
+
+    #[test]
+    fn refuses_cards_into_full_columns() {
+        let mut kanban = limited_board(WipPolicy::Refuse);
+        let before = kanban.clone();
+        let full = Err(KanbanError::ColumnFull(1));
+        assert_eq!(kanban.add_card(1, Card::new("D", "")), full);
+        assert_eq!(kanban.move_card(0, 1, 0), full);
+        assert_eq!(kanban, before);
+
+        // Within the full column itself, a move changes nothing about its count.
+        kanban.add_card(0, Card::new("D", "")).unwrap();
+        kanban.move_card(1, 1, 0).unwrap();
+        assert_eq!(kanban.columns()[1].cards.len(), 1);
+    }
+
+    #[test]
+    fn warns_about_cards_over_the_limit() {
+        let mut kanban = limited_board(WipPolicy::Warn);
+        kanban.add_card(1, Card::new("D", "")).unwrap();
+        kanban.move_card(0, 1, 0).unwrap();
+        let column = &kanban.columns()[1];
+        assert_eq!(column.cards.len(), 3);
+        assert!(column.is_full() && column.is_over_limit());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn writes_timestamps_to_json_as_in_board_files() {
+        let mut card = Card::new("A", "");
+        card.created = "2024-04-20T09:12:44Z".parse().ok();
+        let json = serde_json::to_string(&card).unwrap();
+        assert!(
+            json.contains(r#""created":"2024-04-20T09:12:44Z""#),
+            "{}",
+            json
+        );
+        assert_eq!(serde_json::from_str::<Card>(&json).unwrap(), card);
+        let epoch = json.replace(r#""2024-04-20T09:12:44Z""#, "1713604364");
+        assert!(serde_json::from_str::<Card>(&epoch).is_err());
+    }
+
+    #[test]
+    fn round_trips_limits_and_the_wip_policy() {
+        for policy in [WipPolicy::Warn, WipPolicy::Refuse] {
+            let kanban = limited_board(policy);
+            let lines = kanban
+                .to_string()
+                .lines()
+                .map(String::from)
+                .collect::<Vec<_>>();
+            let (parsed, _) = Kanban::from_lines(&lines, ParseMode::Strict).unwrap();
+            assert_eq!(parsed.wip_policy(), policy);
+            assert_eq!(parsed.columns()[1].limit, Some(1));
+            assert_eq!(parsed, kanban);
         }
+    }
+
+    #[test]
+    fn archives_and_restores_columns_with_their_limit() {
+        let cards = vec![Card::new("A", ""), Card::new("B", "")];
+        let mut kanban = Kanban::with_columns("Board", vec![Column::new("Todo", cards)]);
+        kanban.set_col_limit(0, Some(2)).unwrap();
+        let id = kanban.columns()[0].id.clone();
+        kanban.archive_column(0).unwrap();
+        assert!(kanban.columns().is_empty());
+        assert_eq!(kanban.archive().len(), 2);
+
+        let lines = kanban
+            .to_string()
+            .lines()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        let (parsed, _) = Kanban::from_lines(&lines, ParseMode::Strict).unwrap();
+        assert_eq!(parsed, kanban);
 
-        self.columns[column_idx].cards[item_idx].description = description.to_string();
+        let mut restored = parsed.clone();
+        restored.restore_column(0).unwrap();
+        let column = &restored.columns()[0];
+        assert_eq!((column.id.as_str(), column.limit), (id.as_str(), Some(2)));
+        assert_eq!(column.cards.len(), 2);
+        assert!(restored.archive().is_empty() && restored.archived_columns().is_empty());
+
+        let mut one = parsed;
+        one.restore_card(1, None).unwrap();
+        let column = &one.columns()[0];
+        assert_eq!((column.id.as_str(), column.limit), (id.as_str(), Some(2)));
+        assert_eq!(column.cards[0].title, "B");
+        assert!(one.archived_columns().is_empty());
+        assert_eq!(one.archive().len(), 1);
     }
 
-    // TODO: save to file
-    pub fn save(&self, path: &str) {
-        let _ = save_kanban(self, path);
+    #[test]
+    fn gives_boards_without_ids_the_same_ids_each_load() {
+        let lines = ["Board", "\tTodo", "\t\tA", "\t\tA", "\tDone", "\t\tB"]
+            .map(String::from)
+            .to_vec();
+        let ids = |kanban: &Kanban| {
+            kanban
+                .columns()
+                .iter()
+                .flat_map(|column| {
+                    std::iter::once(column.id.clone())
+                        .chain(column.cards.iter().map(|card| card.id.clone()))
+                })
+                .collect::<Vec<_>>()
+        };
+        let (first, _) = Kanban::from_lines(&lines, ParseMode::Strict).unwrap();
+        let (second, _) = Kanban::from_lines(&lines, ParseMode::Strict).unwrap();
+
+        let first = ids(&first);
+        assert_eq!(first, ids(&second));
+        let unique = first.iter().collect::<std::collections::HashSet<_>>();
+        assert_eq!(unique.len(), first.len());
+    }
+
+    #[test]
+    fn keeps_spaces_around_titles_in_properties() {
+        let mut card = Card::new("Card", "");
+        card.labels = vec![" padded label ".to_string()];
+        let columns = vec![
+            Column::new(" Todo ", vec![card.clone()]),
+            Column::new("", vec![card]),
+        ];
+        let mut kanban = Kanban::with_columns("Board", columns);
+        kanban.add_label(" padded label ", Some("red"));
+        kanban.move_card(0, 1, 0).unwrap();
+        kanban.move_card(1, 0, 1).unwrap();
+        kanban.archive_card(1, 0).unwrap();
+
+        let lines = kanban
+            .to_string()
+            .lines()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        let (parsed, _) = Kanban::from_lines(&lines, ParseMode::Strict).unwrap();
+        assert_eq!(parsed, kanban);
+        assert_eq!(parsed.columns()[0].cards[0].history[1].column, " Todo ");
+        assert_eq!(parsed.archive()[0].column, "");
+        assert_eq!(parsed.labels()[0].name, " padded label ");
     }
 }
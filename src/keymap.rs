@@ -0,0 +1,605 @@
+use std::{collections::HashMap, fmt::Display, str::FromStr};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Where a key is pressed. The same key can mean different actions in different contexts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Context {
+    /// The board itself.
+    Main,
+    /// The card and column editors, in vim normal mode.
+    Editor,
+    /// The checklist pane of the card editor.
+    Checklist,
+    /// The label picker of the card editor and the label filter.
+    Labels,
+    /// The archive browser.
+    Archive,
+    /// The archive confirmation dialogs.
+    Dialog,
+    /// The prompt shown when the board changed both on disk and here.
+    Conflict,
+    /// The search, command line and archive search prompts, where most keys are typed as text.
+    Prompt,
+}
+
+impl Context {
+    /// The context whose keys also work in this one when not taken here.
+    pub fn fallback(&self) -> Option<Context> {
+        match self {
+            Self::Checklist | Self::Labels => Some(Self::Editor),
+            _ => None,
+        }
+    }
+
+    /// Whether a key can reach actions of both contexts, so binding it in both is a clash.
+    fn overlaps(&self, other: Context) -> bool {
+        *self == other || self.fallback() == Some(other) || other.fallback() == Some(*self)
+    }
+}
+
+/// Something a key can be bound to, named in the config file in kebab-case.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Quit,
+    FocusLeft,
+    FocusRight,
+    FocusUp,
+    FocusDown,
+    SwapColumnLeft,
+    SwapColumnRight,
+    MoveCardLeft,
+    MoveCardRight,
+    MoveCardUp,
+    MoveCardDown,
+    EditCard,
+    EditColumn,
+    NewCard,
+    NewColumn,
+    DeleteCard,
+    DeleteColumn,
+    RaisePriority,
+    LowerPriority,
+    Undo,
+    Redo,
+    CommandLine,
+    Search,
+    NextMatch,
+    PreviousMatch,
+    ClearSearch,
+    FilterLabels,
+    ShowDetails,
+    ShowArchive,
+    Save,
+    Cancel,
+    EditTitle,
+    EditDescription,
+    EditChecklist,
+    EditDue,
+    EditLabels,
+    EditLimit,
+    CheckUp,
+    CheckDown,
+    MoveCheckUp,
+    MoveCheckDown,
+    ToggleCheck,
+    AddCheck,
+    EditCheck,
+    DeleteCheck,
+    LeaveChecklist,
+    LabelUp,
+    LabelDown,
+    ToggleLabel,
+    NewLabel,
+    LeaveLabels,
+    ArchiveUp,
+    ArchiveDown,
+    Restore,
+    RestoreHere,
+    Purge,
+    SearchArchive,
+    LeaveArchive,
+    Confirm,
+    Deny,
+    KeepMine,
+    TakeTheirs,
+    Accept,
+    Dismiss,
+    Complete,
+    OlderCommand,
+    NewerCommand,
+    ToggleRegex,
+}
+
+impl Action {
+    pub const ALL: [Action; 68] = [
+        Action::Quit,
+        Action::FocusLeft,
+        Action::FocusRight,
+        Action::FocusUp,
+        Action::FocusDown,
+        Action::SwapColumnLeft,
+        Action::SwapColumnRight,
+        Action::MoveCardLeft,
+        Action::MoveCardRight,
+        Action::MoveCardUp,
+        Action::MoveCardDown,
+        Action::EditCard,
+        Action::EditColumn,
+        Action::NewCard,
+        Action::NewColumn,
+        Action::DeleteCard,
+        Action::DeleteColumn,
+        Action::RaisePriority,
+        Action::LowerPriority,
+        Action::Undo,
+        Action::Redo,
+        Action::CommandLine,
+        Action::Search,
+        Action::NextMatch,
+        Action::PreviousMatch,
+        Action::ClearSearch,
+        Action::FilterLabels,
+        Action::ShowDetails,
+        Action::ShowArchive,
+        Action::Save,
+        Action::Cancel,
+        Action::EditTitle,
+        Action::EditDescription,
+        Action::EditChecklist,
+        Action::EditDue,
+        Action::EditLabels,
+        Action::EditLimit,
+        Action::CheckUp,
+        Action::CheckDown,
+        Action::MoveCheckUp,
+        Action::MoveCheckDown,
+        Action::ToggleCheck,
+        Action::AddCheck,
+        Action::EditCheck,
+        Action::DeleteCheck,
+        Action::LeaveChecklist,
+        Action::LabelUp,
+        Action::LabelDown,
+        Action::ToggleLabel,
+        Action::NewLabel,
+        Action::LeaveLabels,
+        Action::ArchiveUp,
+        Action::ArchiveDown,
+        Action::Restore,
+        Action::RestoreHere,
+        Action::Purge,
+        Action::SearchArchive,
+        Action::LeaveArchive,
+        Action::Confirm,
+        Action::Deny,
+        Action::KeepMine,
+        Action::TakeTheirs,
+        Action::Accept,
+        Action::Dismiss,
+        Action::Complete,
+        Action::OlderCommand,
+        Action::NewerCommand,
+        Action::ToggleRegex,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Quit => "quit",
+            Self::FocusLeft => "focus-left",
+            Self::FocusRight => "focus-right",
+            Self::FocusUp => "focus-up",
+            Self::FocusDown => "focus-down",
+            Self::SwapColumnLeft => "swap-column-left",
+            Self::SwapColumnRight => "swap-column-right",
+            Self::MoveCardLeft => "move-card-left",
+            Self::MoveCardRight => "move-card-right",
+            Self::MoveCardUp => "move-card-up",
+            Self::MoveCardDown => "move-card-down",
+            Self::EditCard => "edit-card",
+            Self::EditColumn => "edit-column",
+            Self::NewCard => "new-card",
+            Self::NewColumn => "new-column",
+            Self::DeleteCard => "delete-card",
+            Self::DeleteColumn => "delete-column",
+            Self::RaisePriority => "raise-priority",
+            Self::LowerPriority => "lower-priority",
+            Self::Undo => "undo",
+            Self::Redo => "redo",
+            Self::CommandLine => "command-line",
+            Self::Search => "search",
+            Self::NextMatch => "next-match",
+            Self::PreviousMatch => "previous-match",
+            Self::ClearSearch => "clear-search",
+            Self::FilterLabels => "filter-labels",
+            Self::ShowDetails => "show-details",
+            Self::ShowArchive => "show-archive",
+            Self::Save => "save",
+            Self::Cancel => "cancel",
+            Self::EditTitle => "edit-title",
+            Self::EditDescription => "edit-description",
+            Self::EditChecklist => "edit-checklist",
+            Self::EditDue => "edit-due",
+            Self::EditLabels => "edit-labels",
+            Self::EditLimit => "edit-limit",
+            Self::CheckUp => "check-up",
+            Self::CheckDown => "check-down",
+            Self::MoveCheckUp => "move-check-up",
+            Self::MoveCheckDown => "move-check-down",
+            Self::ToggleCheck => "toggle-check",
+            Self::AddCheck => "add-check",
+            Self::EditCheck => "edit-check",
+            Self::DeleteCheck => "delete-check",
+            Self::LeaveChecklist => "leave-checklist",
+            Self::LabelUp => "label-up",
+            Self::LabelDown => "label-down",
+            Self::ToggleLabel => "toggle-label",
+            Self::NewLabel => "new-label",
+            Self::LeaveLabels => "leave-labels",
+            Self::ArchiveUp => "archive-up",
+            Self::ArchiveDown => "archive-down",
+            Self::Restore => "restore",
+            Self::RestoreHere => "restore-here",
+            Self::Purge => "purge",
+            Self::SearchArchive => "search-archive",
+            Self::LeaveArchive => "leave-archive",
+            Self::Confirm => "confirm",
+            Self::Deny => "deny",
+            Self::KeepMine => "keep-mine",
+            Self::TakeTheirs => "take-theirs",
+            Self::Accept => "accept",
+            Self::Dismiss => "dismiss",
+            Self::Complete => "complete",
+            Self::OlderCommand => "older-command",
+            Self::NewerCommand => "newer-command",
+            Self::ToggleRegex => "toggle-regex",
+        }
+    }
+
+    pub fn context(&self) -> Context {
+        match self {
+            Self::Save
+            | Self::Cancel
+            | Self::EditTitle
+            | Self::EditDescription
+            | Self::EditChecklist
+            | Self::EditDue
+            | Self::EditLabels
+            | Self::EditLimit => Context::Editor,
+            Self::CheckUp
+            | Self::CheckDown
+            | Self::MoveCheckUp
+            | Self::MoveCheckDown
+            | Self::ToggleCheck
+            | Self::AddCheck
+            | Self::EditCheck
+            | Self::DeleteCheck
+            | Self::LeaveChecklist => Context::Checklist,
+            Self::LabelUp
+            | Self::LabelDown
+            | Self::ToggleLabel
+            | Self::NewLabel
+            | Self::LeaveLabels => Context::Labels,
+            Self::ArchiveUp
+            | Self::ArchiveDown
+            | Self::Restore
+            | Self::RestoreHere
+            | Self::Purge
+            | Self::SearchArchive
+            | Self::LeaveArchive => Context::Archive,
+            Self::Confirm | Self::Deny => Context::Dialog,
+            Self::KeepMine | Self::TakeTheirs => Context::Conflict,
+            Self::Accept
+            | Self::Dismiss
+            | Self::Complete
+            | Self::OlderCommand
+            | Self::NewerCommand
+            | Self::ToggleRegex => Context::Prompt,
+            _ => Context::Main,
+        }
+    }
+
+    /// Whether the action changes the board, and so is refused while the board is read-only.
+    pub fn edits_board(&self) -> bool {
+        matches!(
+            self,
+            Self::SwapColumnLeft
+                | Self::SwapColumnRight
+                | Self::MoveCardLeft
+                | Self::MoveCardRight
+                | Self::MoveCardUp
+                | Self::MoveCardDown
+                | Self::EditCard
+                | Self::EditColumn
+                | Self::NewCard
+                | Self::NewColumn
+                | Self::DeleteCard
+                | Self::DeleteColumn
+                | Self::RaisePriority
+                | Self::LowerPriority
+                | Self::Undo
+                | Self::Redo
+                | Self::Restore
+                | Self::RestoreHere
+                | Self::Purge
+        )
+    }
+
+    /// The keys bound to this action when the config file does not say otherwise.
+    pub fn default_keys(&self) -> &'static [&'static str] {
+        match self {
+            Self::Quit => &["q"],
+            Self::FocusLeft => &["h", "Left"],
+            Self::FocusRight => &["l", "Right"],
+            Self::FocusUp => &["k", "Up"],
+            Self::FocusDown => &["j", "Down"],
+            Self::SwapColumnLeft => &["C-h"],
+            Self::SwapColumnRight => &["C-l"],
+            Self::MoveCardLeft => &["H"],
+            Self::MoveCardRight => &["L"],
+            Self::MoveCardUp => &["K"],
+            Self::MoveCardDown => &["J"],
+            Self::EditCard => &["e", "Enter"],
+            Self::EditColumn => &["E"],
+            Self::NewCard => &["c"],
+            Self::NewColumn => &["C"],
+            Self::DeleteCard => &["d"],
+            Self::DeleteColumn => &["D"],
+            Self::RaisePriority => &["+"],
+            Self::LowerPriority => &["-"],
+            Self::Undo => &["u"],
+            Self::Redo => &["C-r"],
+            Self::CommandLine => &[":"],
+            Self::Search => &["/"],
+            Self::NextMatch => &["n"],
+            Self::PreviousMatch => &["N"],
+            Self::ClearSearch => &["Esc"],
+            Self::FilterLabels => &["t"],
+            Self::ShowDetails => &["i"],
+            Self::ShowArchive => &["a"],
+            Self::Save => &["s", "Enter"],
+            Self::Cancel => &["q", "Esc"],
+            Self::EditTitle => &["C-k"],
+            Self::EditDescription => &["C-j"],
+            Self::EditChecklist => &["C-l"],
+            Self::EditDue => &["C-d"],
+            Self::EditLabels => &["C-t"],
+            Self::EditLimit => &["C-w"],
+            Self::CheckUp => &["k", "Up"],
+            Self::CheckDown => &["j", "Down"],
+            Self::MoveCheckUp => &["K"],
+            Self::MoveCheckDown => &["J"],
+            Self::ToggleCheck => &["x", "Space"],
+            Self::AddCheck => &["a", "o"],
+            Self::EditCheck => &["e", "i"],
+            Self::DeleteCheck => &["d"],
+            Self::LeaveChecklist => &["C-h"],
+            Self::LabelUp => &["k", "Up"],
+            Self::LabelDown => &["j", "Down"],
+            Self::ToggleLabel => &["x", "Space"],
+            Self::NewLabel => &["a", "o"],
+            Self::LeaveLabels => &["C-h"],
+            Self::ArchiveUp => &["k", "Up"],
+            Self::ArchiveDown => &["j", "Down"],
+            Self::Restore => &["r", "Enter"],
+            Self::RestoreHere => &["R"],
+            Self::Purge => &["d"],
+            Self::SearchArchive => &["/"],
+            Self::LeaveArchive => &["q", "Esc"],
+            Self::Confirm => &["y"],
+            Self::Deny => &["n", "q", "Esc", "s", "Enter"],
+            Self::KeepMine => &["m"],
+            Self::TakeTheirs => &["t"],
+            Self::Accept => &["Enter"],
+            Self::Dismiss => &["Esc"],
+            Self::Complete => &["Tab"],
+            Self::OlderCommand => &["Up"],
+            Self::NewerCommand => &["Down"],
+            Self::ToggleRegex => &["C-r"],
+        }
+    }
+}
+
+impl FromStr for Action {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Action::ALL
+            .into_iter()
+            .find(|action| action.name() == s)
+            .ok_or_else(|| format!("unknown action `{}`", s))
+    }
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A key with modifiers, written like `x`, `C-r`, `A-Enter` or `Space` in the config file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let (code, modifiers) = match code {
+            // Shift is already part of an uppercase or symbol character.
+            KeyCode::Char(_) => (code, modifiers - KeyModifiers::SHIFT),
+            // Terminals report Shift+Tab as a key of its own, not always with the shift.
+            KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => {
+                (KeyCode::BackTab, modifiers)
+            }
+            KeyCode::BackTab => (code, modifiers | KeyModifiers::SHIFT),
+            _ => (code, modifiers),
+        };
+
+        Self { code, modifiers }
+    }
+}
+
+impl From<KeyEvent> for KeyChord {
+    fn from(key: KeyEvent) -> Self {
+        KeyChord::new(key.code, key.modifiers)
+    }
+}
+
+impl Display for KeyChord {
+    /// Writes the key the way the config file does.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (modifier, prefix) in [
+            (KeyModifiers::CONTROL, "C-"),
+            (KeyModifiers::ALT, "A-"),
+            (KeyModifiers::SHIFT, "S-"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{}", prefix)?;
+            }
+        }
+
+        match self.code {
+            KeyCode::Enter => write!(f, "Enter"),
+            KeyCode::Esc => write!(f, "Esc"),
+            KeyCode::Tab | KeyCode::BackTab => write!(f, "Tab"),
+            KeyCode::Backspace => write!(f, "Backspace"),
+            KeyCode::Delete => write!(f, "Delete"),
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Left => write!(f, "Left"),
+            KeyCode::Right => write!(f, "Right"),
+            KeyCode::Up => write!(f, "Up"),
+            KeyCode::Down => write!(f, "Down"),
+            KeyCode::Home => write!(f, "Home"),
+            KeyCode::End => write!(f, "End"),
+            KeyCode::PageUp => write!(f, "PageUp"),
+            KeyCode::PageDown => write!(f, "PageDown"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+impl FromStr for KeyChord {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = s;
+        while let Some((modifier, key)) = rest.split_once('-').filter(|(_, key)| !key.is_empty()) {
+            modifiers |= match modifier {
+                "C" => KeyModifiers::CONTROL,
+                "A" => KeyModifiers::ALT,
+                "S" => KeyModifiers::SHIFT,
+                _ => break,
+            };
+            rest = key;
+        }
+
+        let code = match rest {
+            "Enter" => KeyCode::Enter,
+            "Esc" => KeyCode::Esc,
+            "Tab" => KeyCode::Tab,
+            "Backspace" => KeyCode::Backspace,
+            "Delete" => KeyCode::Delete,
+            "Space" => KeyCode::Char(' '),
+            "Left" => KeyCode::Left,
+            "Right" => KeyCode::Right,
+            "Up" => KeyCode::Up,
+            "Down" => KeyCode::Down,
+            "Home" => KeyCode::Home,
+            "End" => KeyCode::End,
+            "PageUp" => KeyCode::PageUp,
+            "PageDown" => KeyCode::PageDown,
+            key => {
+                let mut chars = key.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => return Err(format!("unknown key `{}`", s)),
+                }
+            }
+        };
+
+        Ok(KeyChord::new(code, modifiers))
+    }
+}
+
+/// Which action each key triggers, per context.
+pub struct Keymap {
+    bindings: HashMap<Action, Vec<KeyChord>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = Action::ALL
+            .into_iter()
+            .map(|action| {
+                let keys = action
+                    .default_keys()
+                    .iter()
+                    .map(|key| key.parse().expect("default keys are valid"))
+                    .collect();
+                (action, keys)
+            })
+            .collect();
+
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    /// Replaces the keys bound to each action in `bindings`. A key taken this way is unbound from
+    /// any other action it would clash with, so configured keys always win over the defaults; two
+    /// configured actions sharing a key is an error, since there is no telling which was meant.
+    pub fn bind(&mut self, bindings: Vec<(Action, Vec<KeyChord>)>) -> Result<(), String> {
+        for (i, (action, keys)) in bindings.iter().enumerate() {
+            for (other, other_keys) in &bindings[i + 1..] {
+                if !action.context().overlaps(other.context()) {
+                    continue;
+                }
+                if let Some(key) = keys.iter().find(|key| other_keys.contains(key)) {
+                    return Err(format!(
+                        "`{}` is bound to both `{}` and `{}`",
+                        key, action, other
+                    ));
+                }
+            }
+        }
+
+        for (action, keys) in bindings {
+            for (other, other_keys) in self.bindings.iter_mut() {
+                if *other != action && action.context().overlaps(other.context()) {
+                    other_keys.retain(|key| !keys.contains(key));
+                }
+            }
+            self.bindings.insert(action, keys);
+        }
+
+        Ok(())
+    }
+
+    /// The action bound to `key` in `context`, or in its fallback context, if any.
+    pub fn action(&self, context: Context, key: KeyEvent) -> Option<Action> {
+        let chord = KeyChord::from(key);
+        let bound = |context| {
+            Action::ALL.into_iter().find(|action| {
+                action.context() == context
+                    && self
+                        .bindings
+                        .get(action)
+                        .is_some_and(|keys| keys.contains(&chord))
+            })
+        };
+
+        bound(context).or_else(|| bound(context.fallback()?))
+    }
+
+    /// The first key bound to `action`, written for hints on screen.
+    pub fn key(&self, action: Action) -> String {
+        match self.bindings.get(&action).and_then(|keys| keys.first()) {
+            Some(key) => key.to_string(),
+            None => "unbound".to_string(),
+        }
+    }
+}
@@ -0,0 +1,23 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Colors handed out, in turn, to labels that are created without one.
+pub const LABEL_COLORS: [&str; 6] = ["red", "green", "yellow", "blue", "magenta", "cyan"];
+
+/// An entry in a board's label palette. Cards refer to labels by name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Label {
+    pub name: String,
+    /// A color name or `#rrggbb`, left to the front end to interpret.
+    pub color: String,
+}
+
+impl Label {
+    pub fn new(name: &str, color: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            color: color.to_string(),
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use crabmat::{Card, Kanban};
+use crossterm::event::KeyEvent;
+use ratatui::{
+    layout::Rect,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+
+use crate::{
+    app::{App, CurrentScreen},
+    keymap::{Action, Context},
+    theme::Theme,
+    ui::centered_rect,
+};
+
+/// A chip for each of the card's labels, in its palette color, to put after the title.
+pub fn chips<'a>(card: &'a Card, kanban: &Kanban, theme: &Theme) -> Vec<Span<'a>> {
+    card.labels
+        .iter()
+        .flat_map(|name| {
+            let color = kanban.label(name).map(|label| label.color.as_str());
+            [
+                Span::raw(" "),
+                Span::styled(format!(" {} ", name), theme.label_chip(color)),
+            ]
+        })
+        .collect()
+}
+
+/// Lists `app.labels` with a mark on those in `selection`, highlighting the selected one when
+/// `focused`.
+pub fn render_list(
+    f: &mut Frame,
+    app: &App,
+    area: Rect,
+    block: Block,
+    selection: &[String],
+    focused: bool,
+) {
+    let theme = &app.config.theme;
+    let mut items = app
+        .labels
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let mark = if selection.contains(name) { "x" } else { " " };
+            let color = app.kanban.label(name).map(|label| label.color.as_str());
+            let style = if focused && app.selected_label == i {
+                theme.selected_card
+            } else {
+                theme.card
+            };
+
+            ListItem::new(Line::from(vec![
+                Span::styled(format!(" [{}] ", mark), style),
+                Span::styled(format!(" {} ", name), theme.label_chip(color)),
+            ]))
+        })
+        .collect::<Vec<_>>();
+    if items.is_empty() {
+        items.push(ListItem::new(Line::from(" no labels yet")));
+    }
+
+    let mut state = ListState::default().with_selected(focused.then_some(app.selected_label));
+    f.render_stateful_widget(List::new(items).block(block), area, &mut state);
+}
+
+/// The popup for choosing which labels the board is filtered by.
+pub fn render_label_filter(f: &mut Frame, app: &mut App) {
+    let area = centered_rect(30, 40, f.size());
+    let block = Block::default()
+        .title("Show cards labelled")
+        .borders(Borders::ALL)
+        .border_style(app.config.theme.dialog);
+
+    f.render_widget(Clear, area);
+    render_list(f, app, area, block, &app.label_filter, true);
+}
+
+/// The footer line for an active label filter.
+pub fn footer_line(app: &App) -> Option<Line<'static>> {
+    if app.label_filter.is_empty() {
+        return None;
+    }
+
+    let keymap = &app.config.keymap;
+    Some(Line::from(vec![
+        Span::styled("labels ", app.config.theme.prompt),
+        Span::raw(app.label_filter.join(", ")),
+        Span::styled(
+            format!(
+                "  {} change | {} clear",
+                keymap.key(Action::FilterLabels),
+                keymap.key(Action::ClearSearch)
+            ),
+            app.config.theme.hint,
+        ),
+    ]))
+}
+
+pub fn filter_events(key: KeyEvent, app: &mut App) {
+    match app.config.keymap.action(Context::Labels, key) {
+        Some(Action::LabelDown) => app.increment_selected_label(),
+        Some(Action::LabelUp) => app.decrement_selected_label(),
+        Some(Action::ToggleLabel) => app.toggle_label_filter(),
+        Some(Action::Save | Action::Cancel | Action::LeaveLabels) => {
+            app.current_screen = CurrentScreen::Main;
+        }
+        _ => {}
+    }
+}
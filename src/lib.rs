@@ -0,0 +1,39 @@
+//! The board model and file format behind the `crabmat` terminal kanban board.
+//!
+//! A [`Kanban`] holds [`Column`]s of [`Card`]s, each of which may carry a checklist of
+//! [`Check`]s and [`Label`]s from the board's palette. Boards are read with
+//! [`Kanban::from_file`] and written with [`Kanban::save`] or through their `Display` impl.
+//!
+//! ```no_run
+//! use crabmat::{Card, Kanban};
+//!
+//! let mut kanban = Kanban::from_file("kanban")?;
+//! kanban.add_card(0, Card::new("Write docs", ""))?;
+//! kanban.save("kanban")?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+pub mod check;
+pub mod date;
+pub mod diff;
+pub mod error;
+pub mod format;
+pub mod history;
+pub mod id;
+pub mod io;
+pub mod kanban;
+pub mod label;
+pub mod lock;
+pub mod markdown;
+pub mod merge;
+pub mod priority;
+
+pub use check::Check;
+pub use date::{Date, Due, Time, Timestamp, Urgency};
+pub use error::{KanbanError, LoadError, ParseError, ParseErrorReason};
+pub use history::History;
+pub use kanban::{
+    Archived, ArchivedColumn, Card, Column, Kanban, ParseMode, Transition, WipPolicy,
+};
+pub use label::Label;
+pub use priority::Priority;
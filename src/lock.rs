@@ -0,0 +1,219 @@
+//! Advisory locks that keep two interactive sessions from editing the same board at once.
+//!
+//! The lock is a `.NAME.lock` file next to the board holding the process ID and host name of the
+//! session that owns it. It only works between programs that check for it, and a session that
+//! crashes leaves it behind, which [`Holder::is_stale`] can usually tell.
+
+use std::{
+    fmt::Display,
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::io::resolve;
+
+/// A held lock on a board, released when dropped.
+#[derive(Debug)]
+pub struct Lock {
+    path: PathBuf,
+}
+
+/// Who holds the lock on a board.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Holder {
+    pub pid: u32,
+    pub host: String,
+    /// When the lock was taken, as far as the file system knows.
+    pub since: Option<SystemTime>,
+}
+
+/// Where the lock for the board at `board` is kept.
+pub fn lock_path(board: impl AsRef<Path>) -> PathBuf {
+    let board = board.as_ref();
+    let name = board.file_name().unwrap_or_default().to_string_lossy();
+    board.with_file_name(format!(".{}.lock", name))
+}
+
+/// Locks the board at `board` for this process, or returns who already holds it.
+pub fn acquire(board: &str) -> std::io::Result<Result<Lock, Holder>> {
+    let path = lock_path(resolve(board));
+    match OpenOptions::new().write(true).create_new(true).open(&path) {
+        Ok(mut file) => {
+            writeln!(file, "{} {}", std::process::id(), hostname())?;
+            Ok(Ok(Lock { path }))
+        }
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => match holder(board) {
+            Some(holder) => Ok(Err(holder)),
+            // Released between the two calls.
+            None => acquire(board),
+        },
+        Err(e) => Err(e),
+    }
+}
+
+/// Takes the lock on the board at `board` from whoever holds it.
+pub fn steal(board: &str) -> std::io::Result<Lock> {
+    match fs::remove_file(lock_path(resolve(board))) {
+        Err(e) if e.kind() != ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+
+    match acquire(board)? {
+        Ok(lock) => Ok(lock),
+        Err(holder) => Err(std::io::Error::new(
+            ErrorKind::AlreadyExists,
+            format!("locked again by {}", holder),
+        )),
+    }
+}
+
+/// Who holds the lock on the board at `board`, if anyone. A lock file that cannot be read is
+/// reported with a process ID of 0 and an unknown host.
+pub fn holder(board: &str) -> Option<Holder> {
+    let path = lock_path(resolve(board));
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == ErrorKind::NotFound => return None,
+        Err(_) => String::new(),
+    };
+    let since = fs::metadata(&path).and_then(|meta| meta.modified()).ok();
+
+    let (pid, host) = text.trim().split_once(' ').unwrap_or_default();
+    Some(Holder {
+        pid: pid.parse().unwrap_or(0),
+        host: host.to_string(),
+        since,
+    })
+}
+
+impl Holder {
+    /// Whether the lock was taken on this host.
+    pub fn is_local(&self) -> bool {
+        self.host == hostname()
+    }
+
+    /// Whether the lock was left behind by a process that is no longer running. Only known for
+    /// locks taken on this host; a lock from elsewhere is never considered stale.
+    pub fn is_stale(&self) -> bool {
+        self.is_local() && !is_running(self.pid)
+    }
+}
+
+impl Display for Holder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.pid, self.host.as_str()) {
+            (0, _) | (_, "") => write!(f, "an unknown process"),
+            (pid, host) => write!(f, "process {} on {}", pid, host),
+        }
+    }
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(unix)]
+fn hostname() -> String {
+    let mut buf = [0u8; 256];
+    // SAFETY: `gethostname` writes at most `buf.len()` bytes to `buf`.
+    if unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) } != 0 {
+        return "localhost".to_string();
+    }
+    let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    String::from_utf8_lossy(&buf[..len]).into_owned()
+}
+
+#[cfg(not(unix))]
+fn hostname() -> String {
+    std::env::var("COMPUTERNAME").unwrap_or_else(|_| "localhost".to_string())
+}
+
+#[cfg(unix)]
+fn is_running(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    if pid <= 0 {
+        return false;
+    }
+    // SAFETY: signal 0 only checks whether the process exists.
+    let alive = unsafe { libc::kill(pid, 0) } == 0;
+    // A process owned by someone else cannot be signalled, but is running all the same.
+    alive || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// Without a way to ask, every process is assumed to still be running.
+#[cfg(not(unix))]
+fn is_running(_pid: u32) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory for one test, emptied first in case an earlier run left it behind.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("crabmat-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// The ID of a process that has already exited.
+    #[cfg(unix)]
+    fn dead_pid() -> u32 {
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+        pid
+    }
+
+    #[test]
+    fn refuses_a_held_lock_and_releases_it_when_dropped() {
+        let dir = temp_dir("lock-held");
+        let board = dir.join("kanban").to_string_lossy().into_owned();
+
+        let lock = acquire(&board).unwrap().unwrap();
+        assert!(lock_path(&board).exists());
+        let held = acquire(&board).unwrap().unwrap_err();
+        assert_eq!(held.pid, std::process::id());
+        assert!(held.is_local() && !held.is_stale());
+        assert_eq!(Some(held), holder(&board));
+
+        drop(lock);
+        assert!(holder(&board).is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn steals_a_lock_left_by_a_dead_process() {
+        let dir = temp_dir("lock-stale");
+        let board = dir.join("kanban").to_string_lossy().into_owned();
+        let pid = dead_pid();
+        fs::write(lock_path(&board), format!("{} {}\n", pid, hostname())).unwrap();
+
+        let held = acquire(&board).unwrap().unwrap_err();
+        assert_eq!(held.pid, pid);
+        assert!(held.is_stale());
+
+        let _lock = steal(&board).unwrap();
+        assert_eq!(holder(&board).unwrap().pid, std::process::id());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn never_counts_locks_from_other_hosts_as_stale() {
+        let held = Holder {
+            pid: u32::MAX,
+            host: format!("not-{}", hostname()),
+            since: None,
+        };
+        assert!(!held.is_stale());
+    }
+}
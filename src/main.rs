@@ -1,54 +1,103 @@
 #![allow(dead_code)]
 
-use std::{error::Error, io::Write};
+use std::{
+    error::Error,
+    io::{ErrorKind, Write},
+    time::Duration,
+};
 
+use crabmat::{lock::Holder, Kanban, LoadError};
 use crossterm::{
-    event::{DisableMouseCapture, EnableMouseCapture},
+    event::{self, DisableMouseCapture, EnableMouseCapture},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use events::handle_events;
-use kanban::Kanban;
 use ratatui::{
     backend::{Backend, CrosstermBackend},
     Terminal,
 };
 
 mod app;
-mod check;
+mod archive;
+mod cli;
+mod command;
+mod config;
+mod conflict;
 mod delete_card;
 mod delete_col;
+mod details;
 mod edit_card;
 mod edit_col;
 mod events;
-mod io;
-mod kanban;
+mod keymap;
+mod labels;
+mod search;
+mod theme;
 mod ui;
 mod vim;
 
-use crate::{app::App, ui::ui};
+use crate::{app::App, config::Config, ui::ui};
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let path = std::env::args().nth(1).unwrap_or("kanban".to_string());
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if args.first().is_some_and(|arg| cli::is_command(arg)) {
+        if let Err(e) = cli::run(&args) {
+            eprintln!("crabmat: {}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("crabmat: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let path = args.first().cloned().unwrap_or("kanban".to_string());
     let kanban = match Kanban::from_file(&path) {
         Ok(kanban) => kanban,
-        Err(_) => {
-            print!("Please enter title for board: ");
-            let _ = std::io::stdout().flush();
-            let mut buffer = String::new();
-            let stdin = std::io::stdin();
-            stdin.read_line(&mut buffer)?;
-            Kanban::new(&buffer)
-        },
+        Err(LoadError::Empty) => prompt_for_kanban()?,
+        Err(LoadError::Io(e)) if e.kind() == ErrorKind::NotFound => prompt_for_kanban()?,
+        Err(LoadError::Parse(_)) => {
+            // Never save over a board we could only partially understand.
+            eprintln!(
+                "crabmat: refusing to open {}, it could not be parsed:",
+                path
+            );
+            if let Ok((_, errors)) = Kanban::from_file_lenient(&path) {
+                for error in errors {
+                    eprintln!("  {}", error);
+                }
+            }
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("crabmat: could not read {}: {}", path, e);
+            std::process::exit(1);
+        }
     };
 
+    let mut app = App::new(path, kanban, config);
+    if app.take_lock(false).is_err() {
+        if let Some(holder) = app.locked_by.clone() {
+            if ask_to_steal(&app.path, &holder)? {
+                if let Err(e) = app.take_lock(true) {
+                    eprintln!("crabmat: {}", e);
+                }
+            }
+        }
+    }
+
     enable_raw_mode()?;
     let mut stderr = std::io::stderr();
     execute!(stderr, EnterAlternateScreen, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stderr);
     let mut terminal = Terminal::new(backend)?;
 
-    let mut app = App::new(path, kanban);
     app.save_kanban();
     let _ = run_app(&mut terminal, &mut app);
 
@@ -63,11 +112,55 @@ fn main() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+fn prompt_for_kanban() -> std::io::Result<Kanban> {
+    print!("Please enter title for board: ");
+    let _ = std::io::stdout().flush();
+    let mut buffer = String::new();
+    let stdin = std::io::stdin();
+    stdin.read_line(&mut buffer)?;
+    Ok(Kanban::new(buffer.trim()))
+}
+
+/// Asks whether to take over the lock on `path` from `holder`, which is only worth it when the
+/// holder is gone. A lock held by a live process on this host is left alone without asking.
+fn ask_to_steal(path: &str, holder: &Holder) -> std::io::Result<bool> {
+    let stale = holder.is_stale();
+    if stale {
+        print!(
+            "{} is locked by {}, which is no longer running. Take over the lock? [Y/n] ",
+            path, holder
+        );
+    } else if !holder.is_local() {
+        print!(
+            "{} is locked by {}, which may still be running. Take over the lock anyway? [y/N] ",
+            path, holder
+        );
+    } else {
+        return Ok(false);
+    }
+    let _ = std::io::stdout().flush();
+    let mut buffer = String::new();
+    std::io::stdin().read_line(&mut buffer)?;
+
+    let answer = buffer.trim().to_lowercase();
+    Ok(match answer.as_str() {
+        "y" | "yes" => true,
+        "n" | "no" => false,
+        _ => stale,
+    })
+}
+
+/// How often the board file is checked for changes made elsewhere while no keys are pressed.
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
 fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> std::io::Result<()> {
     let mut stop = false;
     while !stop {
+        app.watch_file();
         terminal.draw(|f| ui(f, app))?;
-        stop = handle_events(app)?;
+        if event::poll(WATCH_INTERVAL)? {
+            stop = handle_events(app)?;
+        }
     }
 
     Ok(())
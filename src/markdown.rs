@@ -0,0 +1,180 @@
+use crate::{
+    error::{LoadError, ParseError, ParseErrorReason},
+    kanban::{Card, Column, Kanban},
+};
+
+/// Writes a board as Markdown: the title as `#`, each column as `##` and each card as a list item
+/// with its description and checklist indented underneath.
+pub fn to_markdown(kanban: &Kanban) -> String {
+    let mut markdown = format!("# {}\n", single_line(kanban.title()));
+
+    for column in kanban.columns() {
+        markdown.push_str(&format!("\n## {}\n", single_line(&column.title)));
+        if !column.cards.is_empty() {
+            markdown.push('\n');
+        }
+
+        for card in &column.cards {
+            markdown.push_str(&format!("- {}\n", single_line(&card.title)));
+            if !card.description.is_empty() {
+                let lines = card.description.split('\n').collect::<Vec<_>>();
+                for (i, line) in lines.iter().enumerate() {
+                    let edge = i == 0 || i == lines.len() - 1;
+                    if line.is_empty() && !edge {
+                        markdown.push('\n');
+                    } else {
+                        markdown.push_str(&format!("  {}\n", escape_line(line)));
+                    }
+                }
+            }
+            for check in &card.checklist {
+                let done = if check.done { "x" } else { " " };
+                markdown.push_str(&format!("  - [{}] {}\n", done, single_line(&check.title)));
+            }
+        }
+    }
+
+    markdown
+}
+
+/// Reads a board written by `to_markdown`, or by hand in the same shape.
+pub fn from_markdown(markdown: &str) -> Result<Kanban, LoadError> {
+    let mut kanban: Option<Kanban> = None;
+    let mut blank_lines = 0;
+    // Whether the last card already has a description line, which may itself be empty.
+    let mut has_description = false;
+
+    for (i, line) in markdown.lines().enumerate() {
+        let error = |reason| ParseError::new(i + 1, line, reason);
+
+        if line.trim().is_empty() {
+            blank_lines += 1;
+            continue;
+        }
+
+        let Some(kanban) = kanban.as_mut() else {
+            let title = line
+                .strip_prefix("# ")
+                .ok_or(error(ParseErrorReason::MissingTitle))?;
+            kanban = Some(Kanban::new(title.trim()));
+            blank_lines = 0;
+            continue;
+        };
+
+        if let Some(title) = line.strip_prefix("## ") {
+            kanban.add_column(Column::new(title.trim(), Vec::new()));
+        } else if let Some(title) = line.strip_prefix("- ").or(line.strip_prefix("* ")) {
+            let column = kanban.columns().len().checked_sub(1);
+            let column = column.ok_or(error(ParseErrorReason::CardWithoutColumn))?;
+            let _ = kanban.add_card(column, Card::new(title.trim(), ""));
+            has_description = false;
+        } else if let Some(text) = line.strip_prefix("  ") {
+            let card = kanban
+                .last_card_mut()
+                .ok_or(error(ParseErrorReason::DescriptionWithoutCard))?;
+            let item = text.trim_start();
+            let check = item
+                .strip_prefix("- [ ] ")
+                .map(|title| (title, false))
+                .or(item.strip_prefix("- [x] ").map(|title| (title, true)))
+                .or(item.strip_prefix("- [X] ").map(|title| (title, true)));
+
+            if let Some((title, done)) = check {
+                card.add_check(title.trim(), done);
+            } else {
+                if has_description {
+                    card.description.push('\n');
+                    for _ in 0..blank_lines {
+                        card.description.push('\n');
+                    }
+                }
+                card.description.push_str(&unescape_line(text));
+                has_description = true;
+            }
+        } else {
+            return Err(error(ParseErrorReason::UnexpectedLine).into());
+        }
+
+        blank_lines = 0;
+    }
+
+    kanban.ok_or(LoadError::Empty)
+}
+
+/// Whether a description line would be read back as something else: a checklist item, a blank
+/// line, or a line escaped this way.
+fn needs_escape(line: &str) -> bool {
+    let item = line.trim_start();
+    item.is_empty()
+        || item.starts_with('\\')
+        || ["- [ ] ", "- [x] ", "- [X] "]
+            .iter()
+            .any(|check| item.starts_with(check))
+}
+
+/// Puts a backslash, which Markdown renders as nothing, in front of a description line that
+/// needs it, after its indentation.
+fn escape_line(line: &str) -> String {
+    if !needs_escape(line) {
+        return line.to_string();
+    }
+    let indent = line.len() - line.trim_start().len();
+    format!("{}\\{}", &line[..indent], &line[indent..])
+}
+
+/// Reverses `escape_line`.
+fn unescape_line(text: &str) -> String {
+    let indent = text.len() - text.trim_start().len();
+    match text[indent..].strip_prefix('\\') {
+        Some(rest) => format!("{}{}", &text[..indent], rest),
+        None => text.to_string(),
+    }
+}
+
+fn single_line(text: &str) -> String {
+    text.replace(['\n', '\r'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::check::Check;
+
+    fn round_trip(description: &str) -> Card {
+        let card = Card::from("Card", description, &[Check::new("Check", true)]);
+        let kanban = Kanban::with_columns("Board", vec![Column::new("Todo", vec![card])]);
+
+        let parsed = from_markdown(&to_markdown(&kanban)).unwrap();
+        parsed.columns()[0].cards[0].clone()
+    }
+
+    #[test]
+    fn keeps_descriptions_that_look_like_checklists() {
+        for description in [
+            "- [x] done",
+            "- [ ] open\n  - [X] nested",
+            "\\- [x] escaped",
+        ] {
+            let card = round_trip(description);
+            assert_eq!(card.description, description);
+            assert_eq!(card.checklist.len(), 1);
+        }
+    }
+
+    #[test]
+    fn keeps_blank_lines_in_descriptions() {
+        for description in ["\n\nfirst\n\n\nlast\n", "\n", "  \nindented\n   "] {
+            assert_eq!(round_trip(description).description, description);
+        }
+    }
+
+    #[test]
+    fn reports_a_missing_title_with_its_line() {
+        let error = from_markdown("\n## Todo\n- Card\n").unwrap_err();
+        let LoadError::Parse(error) = error else {
+            panic!("expected a parse error, got {:?}", error);
+        };
+        assert_eq!(error.line, 2);
+        assert_eq!(error.reason, ParseErrorReason::MissingTitle);
+    }
+}
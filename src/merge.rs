@@ -0,0 +1,886 @@
+//! Three-way merges of boards that match columns and cards by ID rather than by line, so edits to
+//! different cards, or to different parts of one card, never conflict.
+//!
+//! What cannot be merged is settled in favour of `ours`, and described on a conflict card placed
+//! next to what it is about, carrying the [`CONFLICT_LABEL`] label.
+//!
+//! Versions of a board saved without IDs need loading with [`Kanban::from_lines_like`] against
+//! `base`, or every card in them counts as new on both sides.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    check::Check,
+    date::Timestamp,
+    kanban::{Archived, ArchivedColumn, Card, Column, Kanban, Transition},
+};
+
+/// The label put on conflict cards, so they are easy to find.
+pub const CONFLICT_LABEL: &str = "conflict";
+
+/// The result of [`merge`]: the merged board, and a line about each conflict it holds a card for.
+#[derive(Debug, Clone)]
+pub struct Merge {
+    pub kanban: Kanban,
+    pub conflicts: Vec<String>,
+}
+
+/// Where a card is: in the column with this ID, or in the archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Place {
+    Column(String),
+    Archived,
+}
+
+/// A conflict to record on the board, after the card with ID `after` or at the end of the column
+/// with ID `column`, falling back to the first column.
+struct Conflict {
+    title: String,
+    description: String,
+    column: Option<String>,
+    after: Option<String>,
+}
+
+/// The cards that survive a merge, by ID.
+#[derive(Default)]
+struct Cards {
+    /// Where each card ends up.
+    places: HashMap<String, Place>,
+    cards: HashMap<String, Card>,
+    /// The archive entries of those that end up archived.
+    archived: HashMap<String, Archived>,
+}
+
+/// One side of the merge, indexed by ID.
+struct Side<'a> {
+    kanban: &'a Kanban,
+    columns: HashMap<&'a str, &'a Column>,
+    cards: HashMap<&'a str, (Place, &'a Card)>,
+    archived: HashMap<&'a str, &'a Archived>,
+}
+
+impl<'a> Side<'a> {
+    fn new(kanban: &'a Kanban) -> Self {
+        let mut cards = HashMap::new();
+        for column in kanban.columns() {
+            for card in &column.cards {
+                cards.insert(card.id.as_str(), (Place::Column(column.id.clone()), card));
+            }
+        }
+        for archived in kanban.archive() {
+            cards.insert(archived.card.id.as_str(), (Place::Archived, &archived.card));
+        }
+
+        Self {
+            kanban,
+            columns: kanban
+                .columns()
+                .iter()
+                .map(|column| (column.id.as_str(), column))
+                .collect(),
+            cards,
+            archived: kanban
+                .archive()
+                .iter()
+                .map(|archived| (archived.card.id.as_str(), archived))
+                .collect(),
+        }
+    }
+
+    fn column_ids(&self) -> Vec<&'a str> {
+        self.kanban
+            .columns()
+            .iter()
+            .map(|column| column.id.as_str())
+            .collect()
+    }
+
+    /// The IDs of the cards at `place`, in order.
+    fn card_ids(&self, place: &Place) -> Vec<&'a str> {
+        match place {
+            Place::Column(id) => self
+                .columns
+                .get(id.as_str())
+                .map(|column| column.cards.iter().map(|card| card.id.as_str()).collect())
+                .unwrap_or_default(),
+            Place::Archived => self
+                .kanban
+                .archive()
+                .iter()
+                .map(|archived| archived.card.id.as_str())
+                .collect(),
+        }
+    }
+
+    /// What the column with `id` is called, for conflict descriptions.
+    fn place_name(&self, place: &Place) -> String {
+        match place {
+            Place::Column(id) => match self.columns.get(id.as_str()) {
+                Some(column) => column.title.clone(),
+                None => format!("#{}", id),
+            },
+            Place::Archived => "the archive".to_string(),
+        }
+    }
+}
+
+/// The merged value of something both sides may have changed since `base`: whichever side changed
+/// it, or `None` when both did, differently.
+fn pick<T: PartialEq + Clone>(base: Option<&T>, ours: &T, theirs: &T) -> Option<T> {
+    if ours == theirs || base == Some(theirs) {
+        Some(ours.clone())
+    } else if base == Some(ours) {
+        Some(theirs.clone())
+    } else {
+        None
+    }
+}
+
+/// Merges `ours` and `theirs`, both descended from `base`.
+pub fn merge(base: &Kanban, ours: &Kanban, theirs: &Kanban) -> Merge {
+    let (base, ours, theirs) = (Side::new(base), Side::new(ours), Side::new(theirs));
+    let mut conflicts = Vec::new();
+
+    let title = pick(
+        Some(&base.kanban.title().to_string()),
+        &ours.kanban.title().to_string(),
+        &theirs.kanban.title().to_string(),
+    )
+    .unwrap_or_else(|| {
+        conflicts.push(Conflict {
+            title: "Conflict: board title".to_string(),
+            description: format!(
+                "The board was renamed on both sides.\nours: {}\ntheirs: {}",
+                ours.kanban.title(),
+                theirs.kanban.title()
+            ),
+            column: None,
+            after: None,
+        });
+        ours.kanban.title().to_string()
+    });
+    let wip_policy = pick(
+        Some(&base.kanban.wip_policy()),
+        &ours.kanban.wip_policy(),
+        &theirs.kanban.wip_policy(),
+    )
+    .unwrap_or_else(|| {
+        conflicts.push(Conflict {
+            title: "Conflict: WIP policy".to_string(),
+            description: format!(
+                "The WIP policy was changed on both sides.\nours: {}\ntheirs: {}",
+                ours.kanban.wip_policy(),
+                theirs.kanban.wip_policy()
+            ),
+            column: None,
+            after: None,
+        });
+        ours.kanban.wip_policy()
+    });
+    let labels = merge_labels(&base, &ours, &theirs, &mut conflicts);
+
+    let Cards {
+        mut places,
+        cards,
+        mut archived,
+    } = merge_cards(&base, &ours, &theirs, &mut conflicts);
+    let columns = merge_columns(&base, &ours, &theirs, &places, &mut conflicts);
+    let archived_columns = merge_archived_columns(&base, &ours, &theirs, &columns);
+
+    // A card can end up in a column deleted on both sides when it was moved out differently on
+    // each. Rather than lose it, it goes to the archive.
+    for (id, place) in places.iter_mut() {
+        let Place::Column(column) = place else {
+            continue;
+        };
+        if columns.contains_key(column) {
+            continue;
+        }
+        let title = [&base, &ours, &theirs]
+            .iter()
+            .find_map(|side| side.columns.get(column.as_str()))
+            .map(|column| column.title.clone())
+            .unwrap_or_default();
+        archived.entry(id.clone()).or_insert_with(|| Archived {
+            card: cards[id].clone(),
+            column: title,
+            column_id: column.clone(),
+            archived: None,
+        });
+        *place = Place::Archived;
+    }
+
+    let column_order = merge_order(
+        &base.column_ids(),
+        &ours.column_ids(),
+        &theirs.column_ids(),
+        &columns.keys().map(String::as_str).collect(),
+    );
+    let mut board = column_order
+        .iter()
+        .map(|id| {
+            let mut column = columns[*id].clone();
+            let place = Place::Column(id.to_string());
+            let wanted = places
+                .iter()
+                .filter(|(_, p)| **p == place)
+                .map(|(card, _)| card.as_str())
+                .collect();
+            column.cards = merge_order(
+                &base.card_ids(&place),
+                &ours.card_ids(&place),
+                &theirs.card_ids(&place),
+                &wanted,
+            )
+            .into_iter()
+            .map(|card| cards[card].clone())
+            .collect();
+            column
+        })
+        .collect::<Vec<_>>();
+
+    let wanted = places
+        .iter()
+        .filter(|(_, place)| **place == Place::Archived)
+        .map(|(card, _)| card.as_str())
+        .collect();
+    let archive = merge_order(
+        &base.card_ids(&Place::Archived),
+        &ours.card_ids(&Place::Archived),
+        &theirs.card_ids(&Place::Archived),
+        &wanted,
+    )
+    .into_iter()
+    .map(|card| Archived {
+        card: cards[card].clone(),
+        ..archived[card].clone()
+    })
+    .collect::<Vec<_>>();
+
+    let summaries = conflicts
+        .iter()
+        .map(|conflict| conflict.title.clone())
+        .collect::<Vec<_>>();
+    place_conflicts(&mut board, conflicts);
+
+    let mut kanban = Kanban::with_columns(&title, board);
+    kanban.set_wip_policy(wip_policy);
+    for (name, color) in labels {
+        kanban.add_label(&name, Some(&color));
+    }
+    if !summaries.is_empty() && kanban.label(CONFLICT_LABEL).is_none() {
+        kanban.add_label(CONFLICT_LABEL, Some("red"));
+    }
+    kanban.archive_mut().extend(archive);
+    kanban.archived_columns_mut().extend(archived_columns);
+    kanban.assign_ids();
+
+    Merge {
+        kanban,
+        conflicts: summaries,
+    }
+}
+
+/// Merges the label palettes by name, in `ours` order with labels new in `theirs` after them.
+fn merge_labels(
+    base: &Side,
+    ours: &Side,
+    theirs: &Side,
+    conflicts: &mut Vec<Conflict>,
+) -> Vec<(String, String)> {
+    let color = |side: &Side, name: &str| side.kanban.label(name).map(|label| label.color.clone());
+    let names = ours
+        .kanban
+        .labels()
+        .iter()
+        .chain(theirs.kanban.labels())
+        .map(|label| label.name.as_str())
+        .collect::<Vec<_>>();
+
+    let mut labels = Vec::new();
+    let mut seen = HashSet::new();
+    for name in names {
+        if !seen.insert(name) {
+            continue;
+        }
+        let (b, o, t) = (color(base, name), color(ours, name), color(theirs, name));
+        let merged = pick(Some(&b), &o, &t).unwrap_or_else(|| {
+            conflicts.push(Conflict {
+                title: format!("Conflict: label {}", name),
+                description: format!(
+                    "The label was changed on both sides.\nours: {}\ntheirs: {}",
+                    o.as_deref().unwrap_or("removed"),
+                    t.as_deref().unwrap_or("removed")
+                ),
+                column: None,
+                after: None,
+            });
+            o.clone().or(t.clone())
+        });
+        if let Some(color) = merged {
+            labels.push((name.to_string(), color));
+        }
+    }
+
+    labels
+}
+
+/// Merges every card by ID.
+fn merge_cards(base: &Side, ours: &Side, theirs: &Side, conflicts: &mut Vec<Conflict>) -> Cards {
+    let mut ids = ours
+        .cards
+        .keys()
+        .chain(theirs.cards.keys())
+        .collect::<Vec<_>>();
+    ids.sort();
+    ids.dedup();
+
+    let mut merged = Cards::default();
+    for id in ids {
+        let b = base.cards.get(id);
+        let (place, card) = match (ours.cards.get(id), theirs.cards.get(id)) {
+            (Some((o_place, o)), Some((t_place, t))) => {
+                let card = merge_card(b.map(|(_, card)| *card), o, t, conflicts);
+                let place =
+                    pick(b.map(|(place, _)| place), o_place, t_place).unwrap_or_else(|| {
+                        conflicts.push(Conflict {
+                            title: format!("Conflict: {} (moved on both sides)", o.title),
+                            description: format!(
+                                "The card was moved on both sides.\nours: {}\ntheirs: {}",
+                                ours.place_name(o_place),
+                                theirs.place_name(t_place)
+                            ),
+                            column: None,
+                            after: Some(o.id.clone()),
+                        });
+                        o_place.clone()
+                    });
+                (place, card)
+            }
+            (Some((place, card)), None) | (None, Some((place, card))) => {
+                let Some((b_place, b)) = b else {
+                    // New on one side.
+                    merged.places.insert(id.to_string(), place.clone());
+                    merged.cards.insert(id.to_string(), (*card).clone());
+                    continue;
+                };
+                if b_place == place && *b == *card {
+                    // Deleted on one side, untouched on the other.
+                    continue;
+                }
+                let side = if ours.cards.contains_key(id) {
+                    "theirs"
+                } else {
+                    "ours"
+                };
+                conflicts.push(Conflict {
+                    title: format!("Conflict: {} (deleted in {})", card.title, side),
+                    description: format!(
+                        "The card was deleted in {} but changed on the other side, so it was kept.",
+                        side
+                    ),
+                    column: None,
+                    after: Some(card.id.clone()),
+                });
+                (place.clone(), (*card).clone())
+            }
+            (None, None) => continue,
+        };
+
+        if place == Place::Archived {
+            let entry = ours
+                .archived
+                .get(id)
+                .or_else(|| theirs.archived.get(id))
+                .copied();
+            if let Some(entry) = entry {
+                merged.archived.insert(id.to_string(), entry.clone());
+            }
+        }
+        merged.places.insert(id.to_string(), place);
+        merged.cards.insert(id.to_string(), card);
+    }
+
+    merged
+}
+
+/// Merges one card field by field, keeping `ours` for fields both sides changed.
+fn merge_card(
+    base: Option<&Card>,
+    ours: &Card,
+    theirs: &Card,
+    conflicts: &mut Vec<Conflict>,
+) -> Card {
+    let mut clashes = Vec::new();
+    let mut field = |name: &str, ours: String, theirs: String| {
+        clashes.push(format!("{}\nours: {}\ntheirs: {}", name, ours, theirs));
+    };
+    let or_none = |value: Option<String>| value.unwrap_or_else(|| "none".to_string());
+
+    let mut card = ours.clone();
+    match pick(base.map(|card| &card.title), &ours.title, &theirs.title) {
+        Some(title) => card.title = title,
+        None => field("title", ours.title.clone(), theirs.title.clone()),
+    }
+    match pick(
+        base.map(|card| &card.description),
+        &ours.description,
+        &theirs.description,
+    ) {
+        Some(description) => card.description = description,
+        None => field(
+            "description",
+            ours.description.clone(),
+            theirs.description.clone(),
+        ),
+    }
+    match pick(
+        base.map(|card| &card.checklist),
+        &ours.checklist,
+        &theirs.checklist,
+    ) {
+        Some(checklist) => card.checklist = checklist,
+        None => field(
+            "checklist",
+            describe_checklist(&ours.checklist),
+            describe_checklist(&theirs.checklist),
+        ),
+    }
+    match pick(base.map(|card| &card.due), &ours.due, &theirs.due) {
+        Some(due) => card.due = due,
+        None => field(
+            "due",
+            or_none(ours.due.map(|due| due.to_string())),
+            or_none(theirs.due.map(|due| due.to_string())),
+        ),
+    }
+    match pick(
+        base.map(|card| &card.priority),
+        &ours.priority,
+        &theirs.priority,
+    ) {
+        Some(priority) => card.priority = priority,
+        None => field(
+            "priority",
+            or_none(ours.priority.map(|priority| priority.to_string())),
+            or_none(theirs.priority.map(|priority| priority.to_string())),
+        ),
+    }
+
+    // Labels merge as sets: kept by both, or added by either.
+    let base_labels = base.map(|card| card.labels.as_slice()).unwrap_or_default();
+    card.labels = ours
+        .labels
+        .iter()
+        .chain(&theirs.labels)
+        .filter(|label| {
+            (ours.labels.contains(label) && theirs.labels.contains(label))
+                || !base_labels.contains(label)
+        })
+        .fold(Vec::new(), |mut labels, label| {
+            if !labels.contains(label) {
+                labels.push(label.clone());
+            }
+            labels
+        });
+
+    card.created = ours.created.or(theirs.created);
+    card.updated = ours.updated.max(theirs.updated);
+    card.history = merge_history(&ours.history, &theirs.history);
+
+    if !clashes.is_empty() {
+        conflicts.push(Conflict {
+            title: format!("Conflict: {}", card.title),
+            description: format!(
+                "Changed on both sides, the card keeps ours.\n\n{}",
+                clashes.join("\n\n")
+            ),
+            column: None,
+            after: Some(card.id.clone()),
+        });
+    }
+    card
+}
+
+fn describe_checklist(checklist: &[Check]) -> String {
+    if checklist.is_empty() {
+        return "none".to_string();
+    }
+
+    checklist
+        .iter()
+        .map(|check| format!("[{}] {}", if check.done { "x" } else { " " }, check.title))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Both sides' moves, oldest first, without duplicates.
+fn merge_history(ours: &[Transition], theirs: &[Transition]) -> Vec<Transition> {
+    let mut history = ours.to_vec();
+    for transition in theirs {
+        if !history.contains(transition) {
+            history.push(transition.clone());
+        }
+    }
+    history.sort_by_key(|transition| transition.at);
+    history
+}
+
+/// Merges every column by ID, returning the surviving ones without their cards. A column deleted
+/// on one side survives when the other side changed it or merged cards end up in it.
+fn merge_columns(
+    base: &Side,
+    ours: &Side,
+    theirs: &Side,
+    places: &HashMap<String, Place>,
+    conflicts: &mut Vec<Conflict>,
+) -> HashMap<String, Column> {
+    let mut ids = ours
+        .columns
+        .keys()
+        .chain(theirs.columns.keys())
+        .collect::<Vec<_>>();
+    ids.sort();
+    ids.dedup();
+
+    let holds_cards = |id: &str| {
+        places
+            .values()
+            .any(|place| *place == Place::Column(id.to_string()))
+    };
+    let mut columns = HashMap::new();
+    for id in ids {
+        let b = base.columns.get(id).copied();
+        let column = match (ours.columns.get(id), theirs.columns.get(id)) {
+            (Some(o), Some(t)) => {
+                let mut column = Column::new(&o.title, Vec::new());
+                column.id = o.id.clone();
+                match pick(b.map(|column| &column.title), &o.title, &t.title) {
+                    Some(title) => column.title = title,
+                    None => conflicts.push(Conflict {
+                        title: format!("Conflict: column {} (renamed on both sides)", o.title),
+                        description: format!(
+                            "The column was renamed on both sides.\nours: {}\ntheirs: {}",
+                            o.title, t.title
+                        ),
+                        column: Some(o.id.clone()),
+                        after: None,
+                    }),
+                }
+                match pick(b.map(|column| &column.limit), &o.limit, &t.limit) {
+                    Some(limit) => column.limit = limit,
+                    None => {
+                        column.limit = o.limit;
+                        let describe = |limit: Option<usize>| {
+                            limit.map_or("none".to_string(), |limit| limit.to_string())
+                        };
+                        conflicts.push(Conflict {
+                            title: format!("Conflict: column {} (WIP limit)", o.title),
+                            description: format!(
+                                "The WIP limit was changed on both sides.\nours: {}\ntheirs: {}",
+                                describe(o.limit),
+                                describe(t.limit)
+                            ),
+                            column: Some(o.id.clone()),
+                            after: None,
+                        });
+                    }
+                }
+                column
+            }
+            (Some(column), None) | (None, Some(column)) => {
+                let changed = b.is_none_or(|b| b.title != column.title || b.limit != column.limit);
+                if !changed && !holds_cards(id) {
+                    // Deleted on one side, untouched on the other.
+                    continue;
+                }
+                if b.is_some() {
+                    let side = if ours.columns.contains_key(id) {
+                        "theirs"
+                    } else {
+                        "ours"
+                    };
+                    conflicts.push(Conflict {
+                        title: format!("Conflict: column {} (deleted in {})", column.title, side),
+                        description: format!(
+                            "The column was deleted in {} but changed or given cards on the other \
+                             side, so it was kept.",
+                            side
+                        ),
+                        column: Some(column.id.clone()),
+                        after: None,
+                    });
+                }
+                let mut column = (*column).clone();
+                column.cards.clear();
+                column
+            }
+            (None, None) => continue,
+        };
+        columns.insert(id.to_string(), column);
+    }
+
+    columns
+}
+
+/// Merges the archived columns by ID, in `ours` order with those new in `theirs` after them.
+/// Columns back on the merged board are left out, as are those one side restored or deleted for
+/// good while the other left them archived.
+fn merge_archived_columns(
+    base: &Side,
+    ours: &Side,
+    theirs: &Side,
+    columns: &HashMap<String, Column>,
+) -> Vec<ArchivedColumn> {
+    let archived = |side: &Side, id: &str| {
+        side.kanban
+            .archived_columns()
+            .iter()
+            .any(|archived| archived.column.id == id)
+    };
+
+    let mut merged = Vec::new();
+    let mut seen = HashSet::new();
+    for column in ours
+        .kanban
+        .archived_columns()
+        .iter()
+        .chain(theirs.kanban.archived_columns())
+    {
+        let id = column.column.id.as_str();
+        if !seen.insert(id) || columns.contains_key(id) {
+            continue;
+        }
+        if archived(base, id) && !(archived(ours, id) && archived(theirs, id)) {
+            continue;
+        }
+        merged.push(column.clone());
+    }
+
+    merged
+}
+
+/// Orders `wanted` following whichever side reordered the items both sides kept from `base`,
+/// `theirs` when neither did, then slots each remaining item in after the one it follows on the
+/// side it comes from.
+fn merge_order<'a>(
+    base: &[&'a str],
+    ours: &[&'a str],
+    theirs: &[&'a str],
+    wanted: &HashSet<&'a str>,
+) -> Vec<&'a str> {
+    let shared = |items: &[&'a str]| {
+        items
+            .iter()
+            .copied()
+            .filter(|id| {
+                wanted.contains(id) && base.contains(id) && ours.contains(id) && theirs.contains(id)
+            })
+            .collect::<Vec<_>>()
+    };
+    let (first, second) = match shared(ours) == shared(base) {
+        true => (theirs, ours),
+        false => (ours, theirs),
+    };
+
+    let mut order = first
+        .iter()
+        .copied()
+        .filter(|id| wanted.contains(id))
+        .collect::<Vec<_>>();
+    for (i, id) in second.iter().enumerate() {
+        if !wanted.contains(id) || order.contains(id) {
+            continue;
+        }
+        let at = second[..i]
+            .iter()
+            .rev()
+            .find_map(|before| order.iter().position(|kept| kept == before))
+            .map_or(0, |position| position + 1);
+        order.insert(at, id);
+    }
+    // Anything left came from neither side's list, which only happens to cards sent to the
+    // archive because their column is gone.
+    let mut rest = wanted
+        .iter()
+        .copied()
+        .filter(|id| !order.contains(id))
+        .collect::<Vec<_>>();
+    rest.sort();
+    order.extend(rest);
+
+    order
+}
+
+/// Adds a conflict card for each conflict, after the card it is about when that card is on the
+/// board, otherwise at the end of its column or of the first one.
+fn place_conflicts(board: &mut Vec<Column>, conflicts: Vec<Conflict>) {
+    if conflicts.is_empty() {
+        return;
+    }
+    if board.is_empty() {
+        board.push(Column::new("Conflicts", Vec::new()));
+    }
+
+    for conflict in conflicts {
+        let mut card = Card::new(&conflict.title, &conflict.description);
+        card.labels.push(CONFLICT_LABEL.to_string());
+        card.created = Some(Timestamp::now());
+
+        let after = conflict.after.as_deref().and_then(|id| {
+            board.iter().enumerate().find_map(|(i, column)| {
+                let position = column.cards.iter().position(|card| card.id == id)?;
+                Some((i, position + 1))
+            })
+        });
+        let (column, position) = after.unwrap_or_else(|| {
+            let column = conflict
+                .column
+                .as_deref()
+                .and_then(|id| board.iter().position(|column| column.id == id))
+                .unwrap_or(0);
+            (column, board[column].cards.len())
+        });
+        // Skip past conflict cards already placed after the same card.
+        let cards = &board[column].cards;
+        let position = (position..cards.len())
+            .find(|&i| !cards[i].id.is_empty())
+            .unwrap_or(cards.len());
+        board[column].cards.insert(position, card);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ParseMode;
+
+    fn board() -> Kanban {
+        let cards = vec![Card::new("A", ""), Card::new("B", ""), Card::new("C", "")];
+        let columns = vec![
+            Column::new("Todo", cards),
+            Column::new("Doing", Vec::new()),
+            Column::new("Done", Vec::new()),
+        ];
+        Kanban::with_columns("Board", columns)
+    }
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    fn titles(kanban: &Kanban, column: usize) -> Vec<&str> {
+        kanban.columns()[column]
+            .cards
+            .iter()
+            .map(|card| card.title.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn matches_cards_without_ids_by_title() {
+        let base = "Board\n\tTodo\n\t\tA\n\t\tB\n\tDone";
+        let (base, _) = Kanban::from_lines(&lines(base), ParseMode::Strict).unwrap();
+        let like = |text: &str| {
+            Kanban::from_lines_like(&lines(text), ParseMode::Strict, &base)
+                .unwrap()
+                .0
+        };
+        let ours = like("Board\n\tTodo\n\t\tA\n\t\tB\n\tDone\n\t\tC");
+        let theirs = like("Board\n\tTodo\n\t\tA\n\t\tB\n\t\tD\n\tDone");
+
+        let merged = merge(&base, &ours, &theirs);
+        assert!(merged.conflicts.is_empty(), "{:?}", merged.conflicts);
+        assert_eq!(merged.kanban.columns().len(), 2);
+        assert_eq!(titles(&merged.kanban, 0), ["A", "B", "D"]);
+        assert_eq!(titles(&merged.kanban, 1), ["C"]);
+
+        let text = "#!crabmat 3\nBoard\n\tTodo\n\t\t@id abcd\n\t\tA\n\t\t\t@id bcde\n\t\tB";
+        let (base, _) = Kanban::from_lines(&lines(text), ParseMode::Strict).unwrap();
+        let (ours, _) = Kanban::from_lines_like(&lines(text), ParseMode::Strict, &base).unwrap();
+        let merged = merge(&base, &ours, &ours);
+        assert!(merged.conflicts.is_empty(), "{:?}", merged.conflicts);
+        assert_eq!(titles(&merged.kanban, 0), ["A", "B"]);
+    }
+
+    #[test]
+    fn keeps_ours_when_moved_on_both_sides() {
+        let base = board();
+        let (mut ours, mut theirs) = (base.clone(), base.clone());
+        ours.move_card(0, 1, 0).unwrap();
+        theirs.move_card(0, 2, 0).unwrap();
+
+        let merged = merge(&base, &ours, &theirs);
+        assert_eq!(merged.conflicts, ["Conflict: A (moved on both sides)"]);
+        assert_eq!(titles(&merged.kanban, 0), ["B", "C"]);
+        assert_eq!(titles(&merged.kanban, 1)[0], "A");
+        assert!(titles(&merged.kanban, 2).is_empty());
+    }
+
+    #[test]
+    fn keeps_cards_deleted_on_one_side_and_edited_on_the_other() {
+        let base = board();
+        let (mut ours, mut theirs) = (base.clone(), base.clone());
+        ours.delete_card(0, 1).unwrap();
+        ours.delete_card(0, 1).unwrap();
+        theirs.set_card_description(0, 1, "Edited").unwrap();
+
+        let merged = merge(&base, &ours, &theirs);
+        assert_eq!(merged.conflicts, ["Conflict: B (deleted in ours)"]);
+        let cards = &merged.kanban.columns()[0].cards;
+        assert_eq!(cards[0].title, "A");
+        assert_eq!(
+            (cards[1].title.as_str(), cards[1].description.as_str()),
+            ("B", "Edited")
+        );
+        assert!(!cards.iter().any(|card| card.title == "C"));
+    }
+
+    #[test]
+    fn follows_the_side_that_reordered() {
+        let base = board();
+        let (mut ours, mut theirs) = (base.clone(), base.clone());
+        ours.swap_card(0, 0, 2).unwrap();
+        theirs.add_card(0, Card::new("D", "")).unwrap();
+        theirs.swap_column(1, 2).unwrap();
+
+        let merged = merge(&base, &ours, &theirs);
+        assert!(merged.conflicts.is_empty(), "{:?}", merged.conflicts);
+        assert_eq!(titles(&merged.kanban, 0), ["C", "D", "B", "A"]);
+        let columns = merged
+            .kanban
+            .columns()
+            .iter()
+            .map(|column| column.title.as_str());
+        assert_eq!(columns.collect::<Vec<_>>(), ["Todo", "Done", "Doing"]);
+    }
+
+    #[test]
+    fn merges_label_palettes_by_name() {
+        let mut base = board();
+        base.add_label("bug", Some("red"));
+        let (mut ours, mut theirs) = (base.clone(), base.clone());
+        ours.add_label("bug", Some("blue"));
+        ours.add_label("ui", Some("green"));
+        theirs.add_label("bug", Some("yellow"));
+        theirs.add_label("docs", Some("cyan"));
+
+        let merged = merge(&base, &ours, &theirs);
+        assert_eq!(merged.conflicts, ["Conflict: label bug"]);
+        let labels = merged
+            .kanban
+            .labels()
+            .iter()
+            .map(|label| (label.name.as_str(), label.color.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            labels,
+            [
+                ("bug", "blue"),
+                ("ui", "green"),
+                ("docs", "cyan"),
+                (CONFLICT_LABEL, "red")
+            ]
+        );
+    }
+}
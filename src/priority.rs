@@ -0,0 +1,58 @@
+use std::{fmt::Display, str::FromStr};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// How important a card is, from `P0` (drop everything) to `P3` (some day). Orders with the most
+/// important first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Priority {
+    P0,
+    P1,
+    P2,
+    P3,
+}
+
+impl Priority {
+    pub const ALL: [Priority; 4] = [Priority::P0, Priority::P1, Priority::P2, Priority::P3];
+
+    /// The next priority up, starting from `P3` for a card without one.
+    pub fn raise(priority: Option<Priority>) -> Option<Priority> {
+        match priority {
+            None => Some(Priority::P3),
+            Some(Priority::P3) => Some(Priority::P2),
+            Some(Priority::P2) => Some(Priority::P1),
+            Some(Priority::P1 | Priority::P0) => Some(Priority::P0),
+        }
+    }
+
+    /// The next priority down, dropping it altogether after `P3`.
+    pub fn lower(priority: Option<Priority>) -> Option<Priority> {
+        match priority {
+            Some(Priority::P0) => Some(Priority::P1),
+            Some(Priority::P1) => Some(Priority::P2),
+            Some(Priority::P2) => Some(Priority::P3),
+            Some(Priority::P3) | None => None,
+        }
+    }
+}
+
+impl Display for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let level = Priority::ALL.iter().position(|p| p == self).unwrap_or(0);
+        write!(f, "P{}", level)
+    }
+}
+
+impl FromStr for Priority {
+    type Err = ();
+
+    /// Parses `P0` to `P3`, in either case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Priority::ALL
+            .into_iter()
+            .find(|priority| priority.to_string().eq_ignore_ascii_case(s.trim()))
+            .ok_or(())
+    }
+}
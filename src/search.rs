@@ -0,0 +1,151 @@
+use std::ops::Range;
+
+use crabmat::Card;
+use crossterm::event::KeyEvent;
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span},
+};
+use regex::{Regex, RegexBuilder};
+use tui_textarea::Input;
+
+use crate::{
+    app::{App, CurrentScreen},
+    keymap::{Action, Context},
+};
+
+/// A card filter typed after `/` on the main screen.
+pub struct Search {
+    pub query: String,
+    pub regex: bool,
+    matcher: Option<Regex>,
+}
+
+impl Search {
+    /// Builds a search for `query`, treated as a regex when `regex` is set and as plain text
+    /// otherwise. Matching ignores case unless the query contains an uppercase letter.
+    pub fn new(query: &str, regex: bool) -> Self {
+        let pattern = if regex {
+            query.to_string()
+        } else {
+            regex::escape(query)
+        };
+        let matcher = RegexBuilder::new(&pattern)
+            .case_insensitive(!query.chars().any(char::is_uppercase))
+            .build()
+            .ok();
+
+        Self {
+            query: query.to_string(),
+            regex,
+            matcher,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.matcher.is_some()
+    }
+
+    pub fn matches(&self, card: &Card) -> bool {
+        match &self.matcher {
+            Some(matcher) => matcher.is_match(&card.title) || matcher.is_match(&card.description),
+            None => false,
+        }
+    }
+
+    /// Byte ranges of every match in `text`.
+    pub fn ranges(&self, text: &str) -> Vec<Range<usize>> {
+        match &self.matcher {
+            Some(matcher) => matcher
+                .find_iter(text)
+                .filter(|found| !found.is_empty())
+                .map(|found| found.range())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Splits `text` into spans, drawing every match with `highlight` on top of `style`.
+    pub fn highlight<'a>(&self, text: &'a str, style: Style, highlight: Style) -> Vec<Span<'a>> {
+        let mut spans = Vec::new();
+        let mut last = 0;
+        for range in self.ranges(text) {
+            if range.start > last {
+                spans.push(Span::styled(&text[last..range.start], style));
+            }
+            spans.push(Span::styled(&text[range.clone()], style.patch(highlight)));
+            last = range.end;
+        }
+        if last < text.len() || spans.is_empty() {
+            spans.push(Span::styled(&text[last..], style));
+        }
+
+        spans
+    }
+}
+
+/// The footer line for the search prompt or the active filter.
+pub fn footer_line(app: &App) -> Option<Line<'static>> {
+    let (theme, keymap) = (&app.config.theme, &app.config.keymap);
+    let prefix = |regex: bool| if regex { "regex /" } else { "/" };
+
+    if matches!(app.current_screen, CurrentScreen::Search) {
+        let regex = app.search.as_ref().is_some_and(|search| search.regex);
+        return Some(Line::from(vec![
+            Span::styled(prefix(regex), theme.prompt),
+            Span::raw(app.search_input.lines().concat()),
+            Span::styled("█", Style::default().add_modifier(Modifier::SLOW_BLINK)),
+            Span::styled(
+                format!(
+                    "  <{}> regex | {} filter | {} cancel",
+                    keymap.key(Action::ToggleRegex),
+                    keymap.key(Action::Accept),
+                    keymap.key(Action::Dismiss)
+                ),
+                theme.hint,
+            ),
+        ]));
+    }
+
+    let search = app.search.as_ref()?;
+    let status = if search.is_valid() {
+        format!(
+            "  {} matches | {}/{} next/previous | {} clear",
+            app.search_hits().len(),
+            keymap.key(Action::NextMatch),
+            keymap.key(Action::PreviousMatch),
+            keymap.key(Action::ClearSearch)
+        )
+    } else {
+        "  invalid regex".to_string()
+    };
+
+    Some(Line::from(vec![
+        Span::styled(prefix(search.regex), theme.prompt),
+        Span::raw(search.query.clone()),
+        Span::styled(status, theme.hint),
+    ]))
+}
+
+pub fn events(key: KeyEvent, app: &mut App) {
+    let regex = app.search.as_ref().is_some_and(|search| search.regex);
+
+    match app.config.keymap.action(Context::Prompt, key) {
+        Some(Action::Dismiss) => {
+            app.clear_search();
+            app.current_screen = CurrentScreen::Main;
+        }
+        Some(Action::Accept) => {
+            if app.search_input.is_empty() {
+                app.clear_search();
+            }
+            app.current_screen = CurrentScreen::Main;
+        }
+        Some(Action::ToggleRegex) => app.update_search(!regex),
+        _ => {
+            if app.search_input.input(Input::from(key)) {
+                app.update_search(regex);
+            }
+        }
+    }
+}
@@ -0,0 +1,331 @@
+use std::{env, str::FromStr};
+
+use crabmat::{Priority, Urgency};
+use ratatui::style::{Color, Modifier, Style};
+use toml::{Table, Value};
+
+use crate::vim::Mode;
+
+pub const THEMES: [&str; 3] = ["default", "high-contrast", "no-color"];
+
+/// The styles the interface is drawn with, one per named slot.
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub board_title: Style,
+    pub column: Style,
+    pub focused_column: Style,
+    /// The card count of a column holding more cards than its WIP limit.
+    pub over_limit: Style,
+    pub card: Style,
+    pub selected_card: Style,
+    pub progress: Style,
+    pub progress_done: Style,
+    pub due: Style,
+    pub due_this_week: Style,
+    pub due_today: Style,
+    pub overdue: Style,
+    pub label: Style,
+    pub p0: Style,
+    pub p1: Style,
+    pub p2: Style,
+    pub p3: Style,
+    /// Whether label chips are drawn in the label's palette color on top of `label`.
+    pub label_colors: bool,
+    pub mode_normal: Style,
+    pub mode_insert: Style,
+    pub mode_visual: Style,
+    pub mode_operator: Style,
+    pub separator: Style,
+    pub prompt: Style,
+    pub hint: Style,
+    pub search_match: Style,
+    pub dialog: Style,
+    pub danger: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            board_title: Style::default().fg(Color::Green),
+            column: Style::default().fg(Color::DarkGray),
+            focused_column: Style::default().fg(Color::White),
+            over_limit: Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            card: Style::default().fg(Color::DarkGray),
+            selected_card: Style::default()
+                .fg(Color::White)
+                .add_modifier(Modifier::BOLD),
+            progress: Style::default().fg(Color::DarkGray),
+            progress_done: Style::default().fg(Color::Green),
+            due: Style::default().fg(Color::DarkGray),
+            due_this_week: Style::default().fg(Color::Cyan),
+            due_today: Style::default().fg(Color::Yellow),
+            overdue: Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            label: Style::default().fg(Color::Black),
+            p0: Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            p1: Style::default().fg(Color::LightRed),
+            p2: Style::default().fg(Color::Yellow),
+            p3: Style::default().fg(Color::Blue),
+            label_colors: true,
+            mode_normal: Style::default().fg(Color::DarkGray),
+            mode_insert: Style::default().fg(Color::LightBlue),
+            mode_visual: Style::default().fg(Color::LightRed),
+            mode_operator: Style::default().fg(Color::LightGreen),
+            separator: Style::default().fg(Color::White),
+            prompt: Style::default().fg(Color::Yellow),
+            hint: Style::default().fg(Color::DarkGray),
+            search_match: Style::default()
+                .fg(Color::Black)
+                .bg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+            dialog: Style::default().fg(Color::White),
+            danger: Style::default().fg(Color::Red),
+        }
+    }
+}
+
+impl Theme {
+    /// Bright colors only, with the selection shown as a solid bar.
+    pub fn high_contrast() -> Self {
+        let bold = Modifier::BOLD;
+        Self {
+            board_title: Style::default().fg(Color::LightGreen).add_modifier(bold),
+            column: Style::default().fg(Color::Gray),
+            focused_column: Style::default().fg(Color::LightYellow).add_modifier(bold),
+            over_limit: Style::default()
+                .fg(Color::White)
+                .bg(Color::Red)
+                .add_modifier(bold),
+            card: Style::default().fg(Color::White),
+            selected_card: Style::default()
+                .fg(Color::Black)
+                .bg(Color::LightYellow)
+                .add_modifier(bold),
+            progress: Style::default().fg(Color::White),
+            progress_done: Style::default().fg(Color::LightGreen).add_modifier(bold),
+            due: Style::default().fg(Color::White),
+            due_this_week: Style::default().fg(Color::LightCyan),
+            due_today: Style::default().fg(Color::LightYellow).add_modifier(bold),
+            overdue: Style::default()
+                .fg(Color::White)
+                .bg(Color::Red)
+                .add_modifier(bold),
+            label: Style::default().fg(Color::Black).add_modifier(bold),
+            p0: Style::default()
+                .fg(Color::White)
+                .bg(Color::Red)
+                .add_modifier(bold),
+            p1: Style::default().fg(Color::LightRed).add_modifier(bold),
+            p2: Style::default().fg(Color::LightYellow).add_modifier(bold),
+            p3: Style::default().fg(Color::LightCyan),
+            label_colors: true,
+            mode_normal: Style::default().fg(Color::White).add_modifier(bold),
+            mode_insert: Style::default().fg(Color::LightCyan).add_modifier(bold),
+            mode_visual: Style::default().fg(Color::LightMagenta).add_modifier(bold),
+            mode_operator: Style::default().fg(Color::LightGreen).add_modifier(bold),
+            separator: Style::default().fg(Color::White),
+            prompt: Style::default().fg(Color::LightYellow).add_modifier(bold),
+            hint: Style::default().fg(Color::White),
+            search_match: Style::default()
+                .fg(Color::Black)
+                .bg(Color::LightCyan)
+                .add_modifier(bold),
+            dialog: Style::default().fg(Color::White).add_modifier(bold),
+            danger: Style::default().fg(Color::LightRed).add_modifier(bold),
+        }
+    }
+
+    /// No colors at all, only bold, dim and reversed text.
+    pub fn no_color() -> Self {
+        let plain = Style::default();
+        let bold = plain.add_modifier(Modifier::BOLD);
+        let dim = plain.add_modifier(Modifier::DIM);
+        Self {
+            board_title: bold,
+            column: dim,
+            focused_column: bold,
+            over_limit: plain.add_modifier(Modifier::BOLD | Modifier::REVERSED),
+            card: plain,
+            selected_card: plain.add_modifier(Modifier::REVERSED),
+            progress: dim,
+            progress_done: plain,
+            due: dim,
+            due_this_week: plain,
+            due_today: bold,
+            overdue: plain.add_modifier(Modifier::BOLD | Modifier::REVERSED),
+            label: plain.add_modifier(Modifier::REVERSED),
+            p0: plain.add_modifier(Modifier::BOLD | Modifier::REVERSED),
+            p1: bold,
+            p2: plain,
+            p3: dim,
+            label_colors: false,
+            mode_normal: plain,
+            mode_insert: bold,
+            mode_visual: bold,
+            mode_operator: bold,
+            separator: plain,
+            prompt: bold,
+            hint: dim,
+            search_match: plain.add_modifier(Modifier::UNDERLINED | Modifier::BOLD),
+            dialog: plain,
+            danger: bold,
+        }
+    }
+
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Theme::default()),
+            "high-contrast" => Some(Theme::high_contrast()),
+            "no-color" => Some(Theme::no_color()),
+            _ => None,
+        }
+    }
+
+    /// The default theme, or the no-color one when `NO_COLOR` is set.
+    pub fn from_env() -> Self {
+        match env::var_os("NO_COLOR") {
+            Some(value) if !value.is_empty() => Theme::no_color(),
+            _ => Theme::default(),
+        }
+    }
+
+    pub fn urgency(&self, urgency: Urgency) -> Style {
+        match urgency {
+            Urgency::Overdue => self.overdue,
+            Urgency::Today => self.due_today,
+            Urgency::ThisWeek => self.due_this_week,
+            Urgency::Later => self.due,
+        }
+    }
+
+    pub fn priority(&self, priority: Priority) -> Style {
+        match priority {
+            Priority::P0 => self.p0,
+            Priority::P1 => self.p1,
+            Priority::P2 => self.p2,
+            Priority::P3 => self.p3,
+        }
+    }
+
+    /// The style of a label chip for a label with the palette color `color`.
+    pub fn label_chip(&self, color: Option<&str>) -> Style {
+        match color.and_then(|color| Color::from_str(color).ok()) {
+            Some(color) if self.label_colors => self.label.bg(color),
+            _ => self.label.add_modifier(Modifier::REVERSED),
+        }
+    }
+
+    /// The style of the mode indicator in the footer.
+    pub fn mode(&self, mode: Mode) -> Style {
+        match mode {
+            Mode::Normal => self.mode_normal,
+            Mode::Insert => self.mode_insert,
+            Mode::Visual => self.mode_visual,
+            Mode::Operator(_) => self.mode_operator,
+        }
+    }
+
+    fn slot_mut(&mut self, name: &str) -> Option<&mut Style> {
+        let slot = match name {
+            "board-title" => &mut self.board_title,
+            "column" => &mut self.column,
+            "focused-column" => &mut self.focused_column,
+            "over-limit" => &mut self.over_limit,
+            "card" => &mut self.card,
+            "selected-card" => &mut self.selected_card,
+            "progress" => &mut self.progress,
+            "progress-done" => &mut self.progress_done,
+            "due" => &mut self.due,
+            "due-this-week" => &mut self.due_this_week,
+            "due-today" => &mut self.due_today,
+            "overdue" => &mut self.overdue,
+            "label" => &mut self.label,
+            "p0" => &mut self.p0,
+            "p1" => &mut self.p1,
+            "p2" => &mut self.p2,
+            "p3" => &mut self.p3,
+            "mode-normal" => &mut self.mode_normal,
+            "mode-insert" => &mut self.mode_insert,
+            "mode-visual" => &mut self.mode_visual,
+            "mode-operator" => &mut self.mode_operator,
+            "separator" => &mut self.separator,
+            "prompt" => &mut self.prompt,
+            "hint" => &mut self.hint,
+            "search-match" => &mut self.search_match,
+            "dialog" => &mut self.dialog,
+            "danger" => &mut self.danger,
+            _ => return None,
+        };
+
+        Some(slot)
+    }
+
+    /// Applies the `[theme]` section: an optional built-in `name` to start from, then one entry
+    /// per slot, either a color or a table of `fg`, `bg` and modifier flags.
+    pub fn parse(&mut self, mut table: Table) -> Result<(), String> {
+        if let Some(name) = table.remove("name") {
+            let name = name.as_str().ok_or("theme name must be a string")?;
+            *self = Theme::builtin(name).ok_or_else(|| {
+                format!(
+                    "unknown theme `{}`, expected one of {}",
+                    name,
+                    THEMES.join(", ")
+                )
+            })?;
+        }
+
+        if let Some(value) = table.remove("label-colors") {
+            self.label_colors = value
+                .as_bool()
+                .ok_or("label-colors must be true or false")?;
+        }
+
+        for (name, value) in table {
+            let slot = self
+                .slot_mut(&name)
+                .ok_or_else(|| format!("unknown theme slot `{}`", name))?;
+            *slot = parse_style(&name, value)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_color(slot: &str, value: &Value) -> Result<Color, String> {
+    let color = value
+        .as_str()
+        .ok_or_else(|| format!("colors for `{}` must be strings", slot))?;
+    Color::from_str(color).map_err(|_| format!("unknown color `{}` for `{}`", color, slot))
+}
+
+fn parse_style(slot: &str, value: Value) -> Result<Style, String> {
+    let Value::Table(table) = value else {
+        return Ok(Style::default().fg(parse_color(slot, &value)?));
+    };
+
+    let mut style = Style::default();
+    for (key, value) in table {
+        let modifier = match key.as_str() {
+            "fg" => {
+                style = style.fg(parse_color(slot, &value)?);
+                continue;
+            }
+            "bg" => {
+                style = style.bg(parse_color(slot, &value)?);
+                continue;
+            }
+            "bold" => Modifier::BOLD,
+            "dim" => Modifier::DIM,
+            "italic" => Modifier::ITALIC,
+            "underlined" => Modifier::UNDERLINED,
+            "reversed" => Modifier::REVERSED,
+            key => return Err(format!("unknown style key `{}` for `{}`", key, slot)),
+        };
+        match value.as_bool() {
+            Some(true) => style = style.add_modifier(modifier),
+            Some(false) => style = style.remove_modifier(modifier),
+            None => return Err(format!("`{}` for `{}` must be true or false", key, slot)),
+        }
+    }
+
+    Ok(style)
+}
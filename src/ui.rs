@@ -1,6 +1,7 @@
+use crabmat::date;
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Flex, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span, Text},
     widgets::{Block, Borders, List, ListItem, Paragraph},
     Frame,
@@ -8,13 +9,24 @@ use ratatui::{
 
 use crate::{
     app::{App, CurrentScreen},
+    archive::render_archive,
+    command,
+    conflict::render_conflict,
     delete_card::render_delete_card,
     delete_col::render_delete_col,
+    details::render_details,
     edit_card::render_card_screen,
     edit_col::render_edit_col,
+    keymap::Action,
+    labels::{self, render_label_filter},
+    search,
+    vim::Mode,
 };
 
 pub fn ui(f: &mut Frame, app: &mut App) {
+    let theme = app.config.theme.clone();
+    let (today, now) = date::now();
+
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -31,11 +43,15 @@ pub fn ui(f: &mut Frame, app: &mut App) {
         .borders(Borders::ALL)
         .style(Style::default());
 
-    let title = Paragraph::new(Text::styled(
-        app.kanban.title(),
-        Style::default().fg(Color::Green),
-    ))
-    .block(title_block);
+    let mut title = vec![Span::styled(app.kanban.title(), theme.board_title)];
+    if let Some(holder) = &app.locked_by {
+        title.push(Span::raw("  "));
+        title.push(Span::styled(
+            format!(" read-only, open in {} ", holder),
+            theme.danger.add_modifier(Modifier::REVERSED),
+        ));
+    }
+    let title = Paragraph::new(Line::from(title)).block(title_block);
 
     f.render_widget(title, title_layout[0]);
     let cols_block = Block::default()
@@ -45,23 +61,31 @@ pub fn ui(f: &mut Frame, app: &mut App) {
     let cols = Paragraph::new(Text::styled(
         format!(
             "{}/{}",
-            if app.kanban.columns().len() == 0 {
+            if app.kanban.columns().is_empty() {
                 0
             } else {
                 app.selected_column + 1
             },
             app.kanban.columns().len()
         ),
-        Style::default().fg(Color::Green),
+        theme.board_title,
     ))
     .alignment(Alignment::Right)
     .block(cols_block);
 
     f.render_widget(cols, title_layout[1]);
 
+    app.fit_columns(chunks[1].width);
     let column_layout = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints([Constraint::Min(1), Constraint::Min(1), Constraint::Min(1)])
+        .constraints(
+            app.kanban
+                .columns()
+                .iter()
+                .skip(app.columns_start)
+                .take(app.columns_offset)
+                .map(|column| Constraint::Fill(app.config.columns.weight(&column.title))),
+        )
         .split(chunks[1]);
 
     for (i, column) in app
@@ -77,90 +101,132 @@ pub fn ui(f: &mut Frame, app: &mut App) {
         } else {
             &column.title
         };
+        let mut title = vec![Span::raw(title)];
+        if let Some(limit) = column.limit {
+            let count = format!(" {}/{}", column.cards.len(), limit);
+            match column.is_over_limit() {
+                true => title.push(Span::styled(count, theme.over_limit)),
+                false => title.push(Span::raw(count)),
+            }
+        }
+        if app.config.show_ids {
+            title.push(Span::styled(format!(" #{}", column.id), theme.hint));
+        }
         let is_column_selected = i + app.columns_start == app.selected_column;
         let style = if is_column_selected {
-            Style::default().fg(Color::White)
+            theme.focused_column
         } else {
-            Style::default().fg(Color::DarkGray)
+            theme.column
         };
 
         let column_block = Block::default()
-            .title(title)
+            .title(Line::from(title))
             .borders(Borders::ALL)
             .style(style);
         let mut items = Vec::<ListItem>::new();
 
-        if column.cards.len() == 0 {
+        if column.cards.is_empty() {
+            let key = app.config.keymap.key(Action::NewCard);
             items.push(ListItem::new(
-                Line::from("c to create card").alignment(Alignment::Center),
+                Line::from(format!("{} to create card", key)).alignment(Alignment::Center),
             ));
         }
 
         for (j, card) in column.cards.iter().enumerate() {
-            // NOTE: ew, change colors
+            if !app.is_card_visible(i + app.columns_start, j) {
+                continue;
+            }
+
             let style = if is_column_selected && app.selected_card == j {
-                Style::default()
-                    .fg(Color::White)
-                    .add_modifier(Modifier::BOLD)
+                theme.selected_card
             } else {
-                Style::default().fg(Color::DarkGray)
+                theme.card
             };
 
-            items.push(ListItem::new(Line::from(Span::styled(
-                card.title.clone(),
-                style,
-            ))));
-        }
-
-        let list = List::new(items).block(column_block);
-
-        f.render_widget(list, column_layout[i]);
-    }
-
-    let current_navigation_text = vec![
-        match app.vim.mode {
-            crate::vim::Mode::Normal => {
-                Span::styled("NORMAL", Style::default().fg(Color::DarkGray))
-            }
-            crate::vim::Mode::Insert => {
-                Span::styled("INSERT", Style::default().fg(Color::LightBlue))
-            }
-            crate::vim::Mode::Visual => {
-                Span::styled("VISUAL", Style::default().fg(Color::LightRed))
+            let mut line = Vec::new();
+            if app.config.show_ids {
+                line.push(Span::styled(format!("#{} ", card.id), theme.hint));
             }
-            crate::vim::Mode::Operator(_) => {
-                Span::styled("OPERATOR", Style::default().fg(Color::LightGreen))
+            if let Some(priority) = card.priority {
+                line.push(Span::styled(priority.to_string(), theme.priority(priority)));
+                line.push(Span::styled(" ", style));
             }
-        },
-        Span::styled(" | ", Style::default().fg(Color::White)),
-        match app.current_screen {
-            CurrentScreen::Main => {
-                Span::styled("Viewing Board", Style::default().fg(Color::DarkGray))
-            }
-            CurrentScreen::Card(is_new) => {
-                if is_new {
-                    Span::styled("Editing new card", Style::default().fg(Color::Yellow))
-                } else {
-                    Span::styled("Editing card", Style::default().fg(Color::Yellow))
-                }
-            }
-            CurrentScreen::Col(is_new) => {
-                if is_new {
-                    Span::styled("Editing new column", Style::default().fg(Color::Yellow))
-                } else {
-                    Span::styled("Editing column", Style::default().fg(Color::Yellow))
+            match &app.search {
+                Some(search) => {
+                    line.extend(search.highlight(&card.title, style, theme.search_match))
                 }
+                None => line.push(Span::styled(card.title.as_str(), style)),
             }
-            CurrentScreen::DeleteCol(_) => {
-                Span::styled("Deleting column", Style::default().fg(Color::Red))
+            line.extend(labels::chips(card, &app.kanban, &theme));
+            if let Some(due) = &card.due {
+                line.push(Span::styled(
+                    format!(" {}", due),
+                    theme.urgency(due.urgency(today, now)),
+                ));
             }
-            CurrentScreen::DeleteCard(_) => {
-                Span::styled("Deleting card", Style::default().fg(Color::Red))
+            if !card.checklist.is_empty() {
+                let (done, total) = card.progress();
+                line.push(Span::styled(
+                    format!(" {}/{}", done, total),
+                    if done == total {
+                        theme.progress_done
+                    } else {
+                        theme.progress
+                    },
+                ));
             }
+
+            items.push(ListItem::new(Line::from(line)));
         }
-        .to_owned(),
+
+        let list = List::new(items).block(column_block);
+
+        f.render_widget(list, column_layout[i]);
+    }
+
+    let mode = match app.vim.mode {
+        Mode::Normal => "NORMAL",
+        Mode::Insert => "INSERT",
+        Mode::Visual => "VISUAL",
+        Mode::Operator(_) => "OPERATOR",
+    };
+    let (screen, screen_style) = match &app.current_screen {
+        CurrentScreen::Main => ("Viewing Board", theme.hint),
+        CurrentScreen::Card(true) => ("Editing new card", theme.prompt),
+        CurrentScreen::Card(false) => ("Editing card", theme.prompt),
+        CurrentScreen::Col(true) => ("Editing new column", theme.prompt),
+        CurrentScreen::Col(false) => ("Editing column", theme.prompt),
+        CurrentScreen::Search => ("Searching", theme.prompt),
+        CurrentScreen::Command => ("Command", theme.prompt),
+        CurrentScreen::LabelFilter => ("Filtering by label", theme.prompt),
+        CurrentScreen::Details => ("Card details", theme.prompt),
+        CurrentScreen::Archive => ("Archive", theme.prompt),
+        CurrentScreen::Conflict => ("Changed on disk", theme.danger),
+        CurrentScreen::DeleteCol(_) => ("Archiving column", theme.danger),
+        CurrentScreen::DeleteCard(_) => ("Archiving card", theme.danger),
+    };
+    let mut current_navigation_text = vec![
+        Span::styled(mode, theme.mode(app.vim.mode)),
+        Span::styled(" | ", theme.separator),
+        Span::styled(screen, screen_style),
     ];
 
+    if let Some(command) = command::footer_line(app) {
+        current_navigation_text.push(Span::styled(" | ", theme.separator));
+        current_navigation_text.extend(command.spans);
+    } else if let Some(message) = &app.message {
+        current_navigation_text.push(Span::styled(" | ", theme.separator));
+        current_navigation_text.push(Span::styled(message.clone(), theme.prompt));
+    } else {
+        for line in [search::footer_line(app), labels::footer_line(app)]
+            .into_iter()
+            .flatten()
+        {
+            current_navigation_text.push(Span::styled(" | ", theme.separator));
+            current_navigation_text.extend(line.spans);
+        }
+    }
+
     let mode_footer = Paragraph::new(Line::from(current_navigation_text))
         .block(Block::default().borders(Borders::ALL));
 
@@ -172,7 +238,11 @@ pub fn ui(f: &mut Frame, app: &mut App) {
     f.render_widget(mode_footer, footer_chunks[0]);
 
     match &app.current_screen {
-        CurrentScreen::Main => {}
+        CurrentScreen::Main | CurrentScreen::Search | CurrentScreen::Command => {}
+        CurrentScreen::LabelFilter => render_label_filter(f, app),
+        CurrentScreen::Details => render_details(f, app),
+        CurrentScreen::Archive => render_archive(f, app),
+        CurrentScreen::Conflict => render_conflict(f, app),
         CurrentScreen::Card(is_new) => render_card_screen(f, app, *is_new),
         CurrentScreen::Col(is_new) => render_edit_col(f, app, *is_new),
         CurrentScreen::DeleteCard(card_title) => render_delete_card(f, app, card_title.clone()),
@@ -180,6 +250,23 @@ pub fn ui(f: &mut Frame, app: &mut App) {
     }
 }
 
+/// The key bound to `action`, underlined, and what it does. A key that starts `text` is
+/// underlined in place, as in "yes".
+pub fn key_hint(app: &App, action: Action, text: &str) -> Vec<Span<'static>> {
+    let key = app.config.keymap.key(action);
+    let underlined = Style::default().add_modifier(Modifier::UNDERLINED);
+    match text.strip_prefix(key.as_str()) {
+        Some(rest) => vec![
+            Span::styled(key.clone(), underlined),
+            Span::raw(rest.to_string()),
+        ],
+        None => vec![
+            Span::styled(key, underlined),
+            Span::raw(format!(" {}", text)),
+        ],
+    }
+}
+
 pub fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
@@ -3,6 +3,8 @@ use core::fmt;
 use ratatui::{style::{Color, Modifier, Style}, widgets::{Block, Borders}};
 use tui_textarea::{CursorMove, Input, Key, Scrolling, TextArea};
 
+use crate::theme::Theme;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Mode {
     Normal,
@@ -23,12 +25,10 @@ impl Mode {
         Block::default().borders(Borders::ALL).title(title)
     }
 
-    fn cursor_style(&self) -> Style {
+    pub fn cursor_style(&self, theme: &Theme) -> Style {
         let color = match self {
             Self::Normal => Color::Reset,
-            Self::Insert => Color::LightBlue,
-            Self::Visual => Color::LightYellow,
-            Self::Operator(_) => Color::LightGreen,
+            _ => theme.mode(*self).fg.unwrap_or(Color::Reset),
         };
         Style::default().fg(color).add_modifier(Modifier::REVERSED)
     }